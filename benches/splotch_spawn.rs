@@ -0,0 +1,143 @@
+//! Compares the cost of spawning ink splotches with a fresh texture each against shared textures and pooled entities
+//!
+//! `cargo bench --bench splotch_spawn` simulates frames in steady state: every frame spawns a batch of
+//! splotches while older ones fade. Both paths run as systems in the same kind of [`App`] for the same
+//! frames, the current one uses the game's own [`manage_spawns`] and [`tick_splotch`]. It prints the
+//! average frame time and how many splotches a frame could spawn within a 60 fps budget, per approach.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rand::Rng;
+
+use sdk_reborn::player_character::ink::*;
+
+/// Frames a splotch stays alive, shorter than in game so the old path's textures fit in memory
+const LIFETIME_FRAMES: usize = 120;
+/// Simulated time per frame, splotches fade over about `LIFETIME_FRAMES` frames at this step
+const FRAME_TIME: Duration = Duration::from_millis(75);
+const MEASURED_FRAMES: usize = 120;
+const BATCH_SIZES: [usize; 4] = [1, 10, 50, 100];
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+fn main() {
+    println!("{:>10} | {:>22} | {:>22}", "per frame", "per-splotch images", "shared + pooled");
+    for batch in BATCH_SIZES {
+        let before = per_splotch_images(batch);
+        let after = shared_and_pooled(batch);
+        println!("{:>10} | {} | {}", batch, report(before, batch), report(after, batch));
+    }
+}
+
+/// Average frame time, and splotches that fit in one 60 fps frame at that rate
+fn report(frame_time: Duration, batch: usize) -> String {
+    let per_splotch = frame_time.as_secs_f64() / batch as f64;
+    let budget = (FRAME_BUDGET.as_secs_f64() / per_splotch) as u64;
+    return format!("{:>9.3} ms {:>7}/frame", frame_time.as_secs_f64() * 1000.0, budget);
+}
+
+fn random_size(rng: &mut impl Rng) -> usize {
+    return rng.gen_range(20..=70);
+}
+
+/// Splotches requested every frame
+#[derive(Resource)]
+struct Batch(usize);
+
+fn request_splotches (
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    batch: Res<Batch>,
+) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..batch.0 {
+        let size = random_size(&mut rng);
+        spawn_splotch(&mut splotch_registry, size, Vec2::ZERO);
+    }
+}
+
+/// An app with what both paths need, splotches are requested every frame before `systems` run
+fn splotch_app<M>(batch: usize, systems: impl IntoSystemConfigs<M>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.init_resource::<Assets<Image>>();
+    app.init_resource::<SplotchRegistry>();
+    app.insert_resource(Batch(batch));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME));
+    app.add_systems(Update, (request_splotches, systems).chain());
+    return app;
+}
+
+/// Average frame time once the first splotches have faded, so spawning and fading both run every frame
+fn measure(mut app: App) -> Duration {
+    for _ in 0..LIFETIME_FRAMES * 2 {
+        app.update();
+    }
+    let start = Instant::now();
+    for _ in 0..MEASURED_FRAMES {
+        app.update();
+    }
+    return start.elapsed() / MEASURED_FRAMES as u32;
+}
+
+/// The old path, no longer in the game, a Perlin texture generated for every splotch and freed when it fades
+fn per_splotch_images(batch: usize) -> Duration {
+    return measure(splotch_app(batch, (spawn_with_own_image, fade_and_despawn).chain()));
+}
+
+fn spawn_with_own_image (
+    mut commands: Commands,
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut rng = rand::thread_rng();
+    for splotch in splotch_registry.to_spawn.iter() {
+        let size = splotch.size;
+        let splotch_image = images.add(splotch_image(size / 2, rng.gen_range(0..10000)));
+        commands.spawn((
+            Sprite {
+                image: splotch_image.clone(),
+                custom_size: Some(Vec2::new(size as f32, size as f32)),
+                ..default()
+            },
+            Transform::from_translation(splotch.position.extend(0.0)),
+            Splotch {
+                size,
+                timer: Timer::from_seconds(1., TimerMode::Repeating),
+                position: splotch.position,
+                image: splotch_image,
+                effect: SplotchEffect::None,
+            },
+        ));
+    }
+    splotch_registry.to_spawn.clear();
+}
+
+fn fade_and_despawn (
+    mut commands: Commands,
+    mut splotch_query: Query<(&mut Splotch, &mut Sprite, Entity)>,
+    mut images: ResMut<Assets<Image>>,
+    time: Res<Time>,
+) {
+    for (mut splotch, mut sprite, entity) in splotch_query.iter_mut() {
+        splotch.timer.tick(time.delta());
+        if splotch.timer.finished() {
+            let new_alpha = sprite.color.alpha() * 0.75;
+            sprite.color.set_alpha(new_alpha);
+            splotch.timer.reset();
+        }
+        if sprite.color.alpha() < 0.1 {
+            images.remove(splotch.image.id());
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// The current path, the game's splotch systems with shared textures and the entity pool
+fn shared_and_pooled(batch: usize) -> Duration {
+    let mut app = splotch_app(batch, (manage_spawns, tick_splotch).chain());
+    let textures = SplotchTextures::generate(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    app.insert_resource(textures);
+    app.init_resource::<SplotchPool>();
+    return measure(app);
+}
@@ -0,0 +1,267 @@
+//! Cross-checks the assets folder against the asset manifest and the source code
+//!
+//! `cargo run --bin asset_audit` lists files nothing registers, registered names nothing looks up,
+//! names looked up that nothing registers, and manifest problems such as missing paths. Exits with 1
+//! if anything is found. Files and names kept on purpose are listed in [`ALLOWED_UNREGISTERED_FILES`]
+//! and [`ALLOWED_UNUSED_NAMES`], an entry that no longer applies is a problem too.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use sdk_reborn::asset_registry::ASSET_MANIFEST;
+use sdk_reborn::flex_load::*;
+
+/// Files under assets/ that are kept without being registered
+const ALLOWED_UNREGISTERED_FILES: [&str; 8] = [
+    // music not wired up to anything yet
+    "audio/sdkscary.mp3",
+    "audio/sdkscarybossfight.mp3",
+    // reference drawing for the knife holder's masks
+    "knife/knife_holder/diagram.png",
+    // older frames and sheets, squid_map3.png and squid_arrow_0.png replaced them
+    "squid/squid_arrow_1.png",
+    "squid/squid_map.png",
+    "squid/squid_map2.png",
+    // full resolution source of preef.png, which "reef" loads
+    "waterscene/background/reef.png",
+    // water surface shader, not hooked up yet
+    "waterscene/background/watertopshader.wgsl",
+];
+
+/// Registered names nothing looks up yet, kept loaded for code in progress
+const ALLOWED_UNUSED_NAMES: [&str; 4] = ["arrow", "knife", "squid", "walls"];
+
+fn main() -> ExitCode {
+    let root = asset_root();
+    let manifest = match AssetManifest::read(ASSET_MANIFEST) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let manifest_errors = match AssetLoadPlugin::from_manifest(ASSET_MANIFEST) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors.iter().map(|error| error.to_string()).collect::<Vec<String>>(),
+    };
+
+    let registered_paths = manifest.assets.iter()
+        .map(|entry| entry.path.clone())
+        .collect::<HashSet<String>>();
+    let mut unregistered_files = Vec::new();
+    for file in files_in(&root) {
+        let relative = relative_path(&root, &file);
+        if relative != ASSET_MANIFEST && !registered_paths.contains(&relative) {
+            unregistered_files.push(relative);
+        }
+    }
+    unregistered_files.sort();
+    let mut stale_allowances = ALLOWED_UNREGISTERED_FILES.iter()
+        .filter(|file| !unregistered_files.iter().any(|unregistered| unregistered == *file))
+        .map(|file| format!("file \"{}\" is registered or gone", file))
+        .collect::<Vec<String>>();
+    unregistered_files.retain(|file| !ALLOWED_UNREGISTERED_FILES.contains(&file.as_str()));
+
+    let src_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src");
+    let name_uses = find_asset_name_uses(&src_dir);
+    let used_names = name_uses.iter()
+        .map(|name_use| name_use.name.clone())
+        .collect::<HashSet<String>>();
+    let registered_names = manifest.assets.iter()
+        .map(|entry| entry.name.clone())
+        .collect::<HashSet<String>>();
+    let mut unused_names = registered_names.iter()
+        .filter(|name| !used_names.contains(*name))
+        .cloned()
+        .collect::<Vec<String>>();
+    unused_names.sort();
+    stale_allowances.extend(ALLOWED_UNUSED_NAMES.iter()
+        .filter(|name| !unused_names.iter().any(|unused| unused == *name))
+        .map(|name| format!("name \"{}\" is looked up or no longer registered", name)));
+    unused_names.retain(|name| !ALLOWED_UNUSED_NAMES.contains(&name.as_str()));
+    let mut unknown_names = name_uses.iter()
+        .filter(|name_use| !registered_names.contains(&name_use.name))
+        .map(|name_use| format!("\"{}\" at {}:{}", name_use.name, relative_path(&src_dir, &name_use.file), name_use.line))
+        .collect::<Vec<String>>();
+    unknown_names.sort();
+
+    print_section("manifest errors", &manifest_errors);
+    print_section("files in assets/ that are not registered", &unregistered_files);
+    print_section("registered names never looked up in src/", &unused_names);
+    print_section("names looked up in src/ that are not registered", &unknown_names);
+    print_section("allowlist entries that no longer apply", &stale_allowances);
+
+    let problems = manifest_errors.len() + unregistered_files.len() + unused_names.len() + unknown_names.len() + stale_allowances.len();
+    if problems == 0 {
+        println!("asset audit passed");
+        return ExitCode::SUCCESS;
+    }
+    println!("asset audit found {} problem(s)", problems);
+    return ExitCode::FAILURE;
+}
+
+fn print_section(title: &str, lines: &[String]) {
+    if lines.is_empty() {return}
+    println!("{}:", title);
+    for line in lines.iter() {
+        println!("  {}", line);
+    }
+    println!();
+}
+
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {return files};
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(files_in(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    return files;
+}
+
+/// Path relative to the assets folder, with forward slashes like the manifest uses
+fn relative_path(root: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(root).unwrap_or(file);
+    return relative.components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<String>>()
+        .join("/");
+}
+
+/// Methods on [`LoadedAssets`] that take an asset name, used to find names in source code
+const LOOKUP_METHODS: [&str; 4] = ["get_typed", "get_typed_or_default", "get_untyped", "get_atlas"];
+
+/// A string literal passed to one of the [`LoadedAssets`] lookup methods
+struct AssetNameUse {
+    name: String,
+    file: PathBuf,
+    line: usize,
+}
+
+/// Find every literal asset name looked up in the rust files under a folder, commented out lines are skipped
+fn find_asset_name_uses(src_dir: &Path) -> Vec<AssetNameUse> {
+    let mut uses = Vec::new();
+    let Ok(entries) = std::fs::read_dir(src_dir) else {return uses};
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            uses.extend(find_asset_name_uses(&path));
+            continue;
+        }
+        if path.extension().map_or(true, |extension| extension != "rs") {continue}
+        let Ok(contents) = std::fs::read_to_string(&path) else {continue};
+        for (index, line) in contents.lines().enumerate() {
+            for name in asset_names_in_line(strip_line_comment(line)) {
+                uses.push(AssetNameUse { name, file: path.clone(), line: index + 1 });
+            }
+        }
+    }
+    return uses;
+}
+
+/// The code before a line comment, a `//` inside a string literal like a url or path is kept
+fn strip_line_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut previous = ' ';
+    for (index, c) in line.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' && !line[..index].ends_with("'\\") && !line[..index].ends_with('\'') {
+            in_string = true;
+        } else if c == '/' && previous == '/' {
+            return &line[..index - 1];
+        }
+        previous = c;
+    }
+    return line;
+}
+
+fn asset_names_in_line(line: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find(".get_") {
+        rest = &rest[start + 1..];
+        let method_end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        let method = &rest[..method_end];
+        rest = &rest[method_end..];
+        if !LOOKUP_METHODS.contains(&method) {continue}
+        // skip a turbofish like ::<Image>
+        if rest.starts_with("::<") {
+            let Some(end) = rest.find('>') else {break};
+            rest = &rest[end + 1..];
+        }
+        let Some(arguments) = rest.strip_prefix("(\"") else {continue};
+        let Some(end) = arguments.find('"') else {break};
+        names.push(arguments[..end].to_string());
+        rest = &arguments[end..];
+    }
+    return names;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_names_passed_to_lookup_methods () {
+        assert_eq!(asset_names_in_line("let image = loaded.get_typed_or_default::<Image>(\"sand\");"), vec!["sand"]);
+        assert_eq!(asset_names_in_line("loaded.get_atlas(\"squid_map\").unwrap(); loaded.get_untyped(\"knife\")"), vec!["squid_map", "knife"]);
+        // other methods that start with get_ aren't lookups
+        assert!(asset_names_in_line("query.get_single(\"squid\")").is_empty());
+        // only literals are checked
+        assert!(asset_names_in_line("loaded.get_typed::<Image>(name)").is_empty());
+    }
+
+    #[test]
+    fn line_comments_are_stripped_outside_strings () {
+        assert_eq!(strip_line_comment("let a = 1; // loaded.get_atlas(\"old\")"), "let a = 1; ");
+        assert_eq!(strip_line_comment("open(\"https://example.com\"); // note"), "open(\"https://example.com\"); ");
+        assert_eq!(strip_line_comment("let quote = '\"'; // \"comment\""), "let quote = '\"'; ");
+        assert_eq!(strip_line_comment("let escaped = \"a\\\"//b\";"), "let escaped = \"a\\\"//b\";");
+    }
+
+    #[test]
+    fn commented_out_lookups_are_skipped () {
+        let src_dir = std::env::temp_dir().join(format!("asset_audit_test_{}", std::process::id()));
+        std::fs::create_dir_all(src_dir.join("scenes")).unwrap();
+        std::fs::write(src_dir.join("scenes").join("reef.rs"), concat!(
+            "let sand = loaded.get_typed::<Image>(\"sand\");\n",
+            "// let kelp = loaded.get_typed::<Image>(\"kelp\");\n",
+            "let music = loaded.get_untyped(\"music/reef.ogg\"); // loaded.get_atlas(\"squid_map\")\n",
+        )).unwrap();
+        std::fs::write(src_dir.join("notes.txt"), "loaded.get_untyped(\"not_rust\")").unwrap();
+        let uses = find_asset_name_uses(&src_dir);
+        std::fs::remove_dir_all(&src_dir).unwrap();
+
+        let found = uses.iter().map(|name_use| (name_use.name.as_str(), name_use.line)).collect::<Vec<(&str, usize)>>();
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&("sand", 1)));
+        assert!(found.contains(&("music/reef.ogg", 3)));
+    }
+
+    /// Every literal name the code looks up has to be in the manifest
+    #[test]
+    fn names_used_in_source_are_registered () {
+        let manifest = AssetManifest::read(ASSET_MANIFEST).expect("the asset manifest should parse");
+        let registered = manifest.assets.iter().map(|entry| entry.name.as_str()).collect::<HashSet<&str>>();
+        let src_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src");
+        let unknown = find_asset_name_uses(&src_dir).into_iter()
+            .filter(|name_use| !registered.contains(name_use.name.as_str()))
+            .map(|name_use| format!("{}:{} \"{}\"", name_use.file.display(), name_use.line, name_use.name))
+            .collect::<Vec<String>>();
+        assert!(unknown.is_empty(), "asset names looked up but not registered:\n{}", unknown.join("\n"));
+    }
+}
@@ -4,6 +4,9 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::flex_load::*;
+use crate::player_character::player::{Health, Player};
+use crate::player_character::tentacle::{Grabbable, Grabbed};
+use crate::player_character::ink::{InkCloud, hidden_by_ink};
 
 
 pub struct RatPlugin;
@@ -11,7 +14,7 @@ pub struct RatPlugin;
 impl Plugin for RatPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AssetLoadState::Ready), spawn_rat);
-        app.add_systems(Update, rat_movement.run_if(in_state(AssetLoadState::Ready)));
+        app.add_systems(Update, (rat_movement, despawn_dead_rats).run_if(in_state(AssetLoadState::Ready)));
     }
 }
 
@@ -48,22 +51,21 @@ impl DirectionTimer {
 fn spawn_rat (
     mut commands: Commands,
     loaded: Res<LoadedAssets>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
-    let rat_map_layout = texture_atlas_layouts.add(
-        TextureAtlasLayout::from_grid(UVec2::new(27, 20), 2, 2, None, None)
-    );
+    let rat_map = match loaded.get_atlas("rat_map") {
+        Ok(atlas) => atlas,
+        Err(error) => {
+            error!("could not spawn a rat: {}", error);
+            return;
+        }
+    };
     // write your code here
     commands.spawn((
         Rat,
         Sprite {
-            image: loaded.get_typed_clone::<Image>("rat_map").unwrap(),
+            image: rat_map.image.clone(),
             custom_size: Some(Vec2::new(54.0, 40.0)),
-            texture_atlas: 
-                Some( TextureAtlas {
-                    layout: rat_map_layout.clone(),
-                    index: 0,
-                }),
+            texture_atlas: Some(rat_map.texture_atlas(0)),
             ..default()
         },
         RigidBody::Dynamic,
@@ -72,17 +74,40 @@ fn spawn_rat (
         Transform::from_translation(Vec3::new(-5.,0., 0.)),
         GravityScale(1.0),
         DirectionTimer::new(2.0),
+        Health::new(30.0),
+        Grabbable,
         LockedAxes::ROTATION_LOCKED
 
     ));
     }
+/// Speed a rat walks back and forth at, pixels per second
+const RAT_PATROL_SPEED: f32 = 50.0;
+/// Speed a rat runs at the squid while it can see it, pixels per second
+const RAT_CHASE_SPEED: f32 = 80.0;
+/// How far a rat can see the squid from, pixels
+const RAT_SIGHT: f32 = 250.0;
+
 fn rat_movement(
     time: Res<Time>,
-    mut rat_query: Query<(&mut Sprite, &mut Velocity, &mut DirectionTimer), With<Rat>>,
+    // the tentacle steers grabbed rats, see tentacle.rs
+    mut rat_query: Query<(&mut Sprite, &mut Velocity, &mut DirectionTimer, &Transform), (With<Rat>, Without<Grabbed>)>,
+    player_query: Query<&Transform, (With<Player>, Without<Rat>)>,
+    cloud_query: Query<(&InkCloud, &Transform)>,
 ){
 
-    for (mut rat_sprite, mut rat_velocity, mut timer) in rat_query.iter_mut(){
-        
+    for (mut rat_sprite, mut rat_velocity, mut timer, rat_transform) in rat_query.iter_mut(){
+
+        // chase the squid while it's in sight, ink clouds break the line of sight
+        let rat_position = rat_transform.translation.xy();
+        let target = player_query.get_single().ok().map(|transform| transform.translation.xy())
+            .filter(|position| position.distance(rat_position) < RAT_SIGHT && !hidden_by_ink(&cloud_query, rat_position, *position));
+        if let Some(position) = target {
+            timer.right_face = position.x > rat_position.x;
+            rat_velocity.linvel.x = if timer.right_face {RAT_CHASE_SPEED} else {-RAT_CHASE_SPEED};
+            rat_sprite.flip_x = timer.right_face;
+            continue;
+        }
+
         timer.timer.tick(time.delta());
         if timer.timer.finished() {
             timer.right_face = !timer.right_face;
@@ -90,10 +115,10 @@ fn rat_movement(
         }
 
         if(timer.right_face){
-            rat_velocity.linvel.x = 50.0;
+            rat_velocity.linvel.x = RAT_PATROL_SPEED;
             rat_sprite.flip_x = true;
         } else{
-            rat_velocity.linvel.x = -50.0;
+            rat_velocity.linvel.x = -RAT_PATROL_SPEED;
             rat_sprite.flip_x = false;
         }    
     }
@@ -104,3 +129,13 @@ fn rat_movement(
 
 }
 
+fn despawn_dead_rats (
+    mut commands: Commands,
+    rat_query: Query<(Entity, &Health), With<Rat>>,
+) {
+    for (entity, health) in rat_query.iter() {
+        if health.is_dead() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use bevy::prelude::*;
-use std::collections::HashMap;
-use bevy::asset::{LoadState, UntypedHandle};
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use bevy::asset::{LoadState, UntypedAssetId, UntypedHandle};
+use bevy::asset::io::file::FileAssetReader;
+use serde::Deserialize;
 
 pub struct AssetLoadPlugin(LoadedAssets);
 
@@ -8,104 +14,1067 @@ impl AssetLoadPlugin {
     pub fn new() -> Self {
         return Self(LoadedAssets::new());
     }
+    /// Add an asset to the [`DEFAULT_GROUP`]
     pub fn add_asset<T: Asset>(&mut self, name: &str, path: &str) {
-        self.0.add_asset::<T>(name, path);
+        self.0.add_asset::<T>(DEFAULT_GROUP, name, path);
+    }
+    /// Add an asset that falls back to a placeholder handle if it fails to load
+    pub fn add_optional_asset<T: Asset>(&mut self, name: &str, path: &str) {
+        self.0.add_asset::<T>(DEFAULT_GROUP, name, path);
+        self.0.optional.insert(name.to_string());
+    }
+    /// Add a sprite sheet, its layout is built from the grid when its group is requested, without waiting for the image
+    pub fn add_atlas(&mut self, name: &str, path: &str, grid: AtlasGrid) {
+        self.0.add_asset::<Image>(DEFAULT_GROUP, name, path);
+        self.0.atlas_grids.insert(name.to_string(), grid);
+    }
+    /// Add an asset to a named group, the group is only loaded once requested
+    pub fn add_group_asset<T: Asset>(&mut self, group: &str, name: &str, path: &str) {
+        self.0.add_asset::<T>(group, name, path);
+    }
+    /// Load a group at startup, [`AssetLoadState::Ready`] waits for every preloaded group
+    pub fn preload_group(&mut self, group: &str) {
+        if !self.0.preload.iter().any(|preloaded| preloaded == group) {
+            self.0.preload.push(group.to_string());
+        }
+    }
+    /// Build the plugin from a manifest file, path is relative to the assets folder
+    ///
+    /// Every problem in the manifest is collected so they can all be reported at once
+    pub fn from_manifest(manifest_path: &str) -> Result<Self, Vec<ManifestError>> {
+        let manifest = AssetManifest::read(manifest_path).map_err(|error| vec![error])?;
+        return Self::from_parsed_manifest(&manifest);
+    }
+    fn from_parsed_manifest(manifest: &AssetManifest) -> Result<Self, Vec<ManifestError>> {
+        let mut plugin = Self::new();
+        plugin.0.preload.clear();
+        for group in manifest.preload.iter() {
+            plugin.preload_group(group);
+        }
+        let mut errors = Vec::new();
+        for entry in manifest.assets.iter() {
+            if let Err(error) = plugin.add_manifest_entry(entry) {
+                errors.push(error);
+            }
+        }
+        if errors.is_empty() {
+            return Ok(plugin);
+        }
+        return Err(errors);
+    }
+    fn add_manifest_entry(&mut self, entry: &ManifestEntry) -> Result<(), ManifestError> {
+        if self.0.asset_links.contains_key(&entry.name) {
+            return Err(ManifestError::DuplicateName { name: entry.name.clone() });
+        }
+        let Some(asset_type) = asset_type_for_kind(&entry.kind) else {
+            return Err(ManifestError::UnknownKind { name: entry.name.clone(), kind: entry.kind.clone() });
+        };
+        let full_path = asset_root().join(&entry.path);
+        if !full_path.is_file() {
+            return Err(ManifestError::MissingFile { name: entry.name.clone(), path: full_path });
+        }
+        if entry.atlas.is_some() && entry.kind != "image" {
+            return Err(ManifestError::AtlasOnNonImage { name: entry.name.clone(), kind: entry.kind.clone() });
+        }
+        self.0.insert_asset(&entry.group, &entry.name, &entry.path, asset_type);
+        if entry.optional {
+            self.0.optional.insert(entry.name.clone());
+        }
+        if let Some(grid) = entry.atlas {
+            self.0.atlas_grids.insert(entry.name.clone(), grid);
+        }
+        return Ok(());
     }
 }
 
 impl Plugin for AssetLoadPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(self.0.clone());
+        let mut loaded_assets = self.0.clone();
+        for group in self.0.preload.iter() {
+            loaded_assets.request_group(group);
+        }
+        app.insert_resource(loaded_assets);
         app.init_state::<AssetLoadState>();
-        app.add_systems(Startup, load);
-        app.add_systems(Update, verify_load.run_if(in_state(AssetLoadState::Loading)));
+        app.init_resource::<AssetLoadFailures>();
+        app.add_event::<AssetGroupReady>();
+        app.add_systems(Update, (load_requested_groups, verify_load).chain());
+    }
+}
+
+/// Development mode that keeps [`LoadedAssets`] in sync with the files on disk
+///
+/// Changed asset files are reloaded in place by bevy's file watcher (the `hot_reload` feature),
+/// and edits to the manifest are applied without going back to [`AssetLoadState::Loading`]
+pub struct AssetHotReloadPlugin {
+    /// Manifest path relative to the assets folder
+    pub manifest_path: String,
+}
+
+impl Plugin for AssetHotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ManifestWatcher {
+            manifest_path: self.manifest_path.clone(),
+            file: FileWatcher::new(asset_root().join(&self.manifest_path)),
+            swaps: Vec::new(),
+        });
+        app.add_systems(Update, (
+            reload_changed_manifest.after(verify_load),
+            swap_sprite_images,
+            log_image_reloads,
+        ).chain().run_if(not(in_state(AssetLoadState::Loading))));
+    }
+}
+
+#[derive(Resource)]
+struct ManifestWatcher {
+    manifest_path: String,
+    file: FileWatcher,
+    /// Handles replaced because the manifest now points somewhere else, old id to new handle
+    swaps: Vec<(UntypedAssetId, UntypedHandle)>,
+}
+
+/// Polls a file's modification time once a second, for files bevy's asset watcher doesn't cover
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+impl FileWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            modified: modified_time(&path),
+            path,
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+    pub fn path(&self) -> &PathBuf {
+        return &self.path;
+    }
+    /// Advance the poll timer, true when a poll finds the file changed since the last one
+    ///
+    /// Tick it with real time so edits are picked up while the game is paused
+    pub fn changed(&mut self, delta: std::time::Duration) -> bool {
+        self.timer.tick(delta);
+        if !self.timer.just_finished() {return false}
+        let modified = modified_time(&self.path);
+        if modified == self.modified {return false}
+        self.modified = modified;
+        return true;
+    }
+}
+
+fn modified_time(path: &PathBuf) -> Option<SystemTime> {
+    return std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+}
+
+fn reload_changed_manifest (
+    mut watcher: ResMut<ManifestWatcher>,
+    mut loaded_assets: ResMut<LoadedAssets>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    time: Res<Time<Real>>,
+) {
+    if !watcher.file.changed(time.delta()) {return}
+
+    match AssetLoadPlugin::from_manifest(&watcher.manifest_path) {
+        Ok(reloaded) => {
+            let swaps = loaded_assets.apply_reloaded(&reloaded.0, &asset_server, &mut texture_atlas_layouts);
+            info!("reloaded asset manifest, {} asset(s) now point to new files", swaps.len());
+            watcher.swaps.extend(swaps);
+        },
+        Err(errors) => {
+            for error in errors.iter() {
+                error!("asset manifest not reloaded: {}", error);
+            }
+        },
+    }
+}
+
+fn swap_sprite_images (
+    mut watcher: ResMut<ManifestWatcher>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    if watcher.swaps.is_empty() {return}
+    for mut sprite in sprite_query.iter_mut() {
+        let Some((_, new_handle)) = watcher.swaps.iter().find(|(old_id, _)| *old_id == sprite.image.id().untyped()) else {continue};
+        if let Ok(image) = new_handle.clone().try_typed::<Image>() {
+            sprite.image = image;
+        }
+    }
+    watcher.swaps.clear();
+}
+
+fn log_image_reloads (
+    mut events: EventReader<AssetEvent<Image>>,
+    loaded_assets: Res<LoadedAssets>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let Some(name) = loaded_assets.name_of(id.untyped()) {
+                info!("reloaded image \"{}\"", name);
+            }
+        }
     }
 }
 
+/// Group used for assets that don't name one
+pub const DEFAULT_GROUP: &str = "common";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetGroupStatus {
+    /// Not requested, or released
+    Unloaded,
+    /// Requested, handles are created on the next update
+    Requested,
+    Loading,
+    Ready,
+    /// At least one required asset in the group failed, see [`AssetLoadFailures`]
+    Failed,
+}
+
+#[derive(Clone)]
+struct AssetGroup {
+    members: Vec<String>,
+    status: AssetGroupStatus,
+    count_loaded: usize,
+}
+
+/// Sent once every asset in a group has loaded
+#[derive(Event, Clone, Debug)]
+pub struct AssetGroupReady {
+    pub group: String,
+}
+
+/// Run condition for systems that should only run once a group has loaded
+pub fn asset_group_ready(group: &'static str) -> impl Fn(Res<LoadedAssets>) -> bool {
+    move |loaded_assets: Res<LoadedAssets>| loaded_assets.is_group_ready(group)
+}
+
+/// Run condition that is true once every time a group finishes loading, for spawning what uses it
+pub fn asset_group_just_ready(group: &'static str) -> impl Fn(EventReader<AssetGroupReady>) -> bool {
+    move |mut ready_events: EventReader<AssetGroupReady>| ready_events.read().filter(|event| event.group == group).count() > 0
+}
+
 #[derive(Resource, Clone)]
 pub struct LoadedAssets {
     pub asset_map: HashMap<String, UntypedHandle>,
     asset_links: HashMap<String, String>,
-    load_functions: HashMap<String, AssetLoader>,
-    count_loaded: usize,
+    asset_types: HashMap<String, AssetTypeInfo>,
+    optional: HashSet<String>,
+    using_placeholder: HashSet<String>,
+    atlas_grids: HashMap<String, AtlasGrid>,
+    atlas_layouts: HashMap<String, Handle<TextureAtlasLayout>>,
+    groups: HashMap<String, AssetGroup>,
+    preload: Vec<String>,
+    currently_loading: Option<String>,
 }
 
 type AssetLoader = fn(&AssetServer, String) -> UntypedHandle;
 fn load_function<T: Asset>(asset_server: &AssetServer, path: String) -> UntypedHandle {
     return asset_server.load::<T>(path).into();
 }
+type PlaceholderGetter = fn() -> UntypedHandle;
+fn placeholder_function<T: Asset>() -> UntypedHandle {
+    return Handle::<T>::default().untyped();
+}
+
+/// Everything needed to load an asset without knowing its type, recorded when it is added
+#[derive(Clone, Copy)]
+struct AssetTypeInfo {
+    load: AssetLoader,
+    placeholder: PlaceholderGetter,
+    type_id: TypeId,
+    type_name: &'static str,
+}
+
+impl AssetTypeInfo {
+    fn of<T: Asset>() -> Self {
+        Self {
+            load: load_function::<T>,
+            placeholder: placeholder_function::<T>,
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+        }
+    }
+}
+
+/// Maps the `kind` field of a manifest entry to the asset type it is loaded as
+fn asset_type_for_kind(kind: &str) -> Option<AssetTypeInfo> {
+    match kind {
+        "image" => Some(AssetTypeInfo::of::<Image>()),
+        "audio" => Some(AssetTypeInfo::of::<AudioSource>()),
+        "shader" => Some(AssetTypeInfo::of::<Shader>()),
+        _ => None,
+    }
+}
 
 impl LoadedAssets {
     pub fn new() -> Self {
         Self {
             asset_map: HashMap::default(),
             asset_links: HashMap::default(),
-            load_functions: HashMap::default(),
-            count_loaded: 0,
+            asset_types: HashMap::default(),
+            optional: HashSet::default(),
+            using_placeholder: HashSet::default(),
+            atlas_grids: HashMap::default(),
+            atlas_layouts: HashMap::default(),
+            groups: HashMap::default(),
+            preload: vec![DEFAULT_GROUP.to_string()],
+            currently_loading: None,
         }
     }
 
-    fn add_asset<T: Asset>(&mut self, name: &str, path: &str) {
+    fn add_asset<T: Asset>(&mut self, group: &str, name: &str, path: &str) {
+        self.insert_asset(group, name, path, AssetTypeInfo::of::<T>());
+    }
+    fn insert_asset(&mut self, group: &str, name: &str, path: &str, asset_type: AssetTypeInfo) {
         self.asset_links.insert(name.to_string(), path.to_string());
-        self.load_functions.insert(name.to_string(), load_function::<T>);
+        self.asset_types.insert(name.to_string(), asset_type);
+        self.join_group(group, name);
+    }
+    /// Add an asset to a group, a ready group stays ready and counts it as loaded
+    ///
+    /// The caller loads its handle if the group is loaded, a failed group checks its assets again
+    fn join_group(&mut self, group: &str, name: &str) {
+        let group = self.groups.entry(group.to_string()).or_insert(AssetGroup {
+            members: Vec::new(),
+            status: AssetGroupStatus::Unloaded,
+            count_loaded: 0,
+        });
+        group.members.push(name.to_string());
+        match group.status {
+            AssetGroupStatus::Ready => group.count_loaded = group.members.len(),
+            AssetGroupStatus::Failed => group.status = AssetGroupStatus::Loading,
+            _ => {},
+        }
     }
-    /// Get the number of assets that have been added
+    /// The preloaded groups, the ones [`AssetLoadState::Ready`] waits for
+    fn preloaded_groups(&self) -> impl Iterator<Item = &AssetGroup> {
+        return self.preload.iter().filter_map(|group| self.groups.get(group));
+    }
+    /// Get the number of assets in every preloaded group
     pub fn count(&self) -> usize {
-        return self.asset_links.len();
+        return self.preloaded_groups()
+            .filter(|group| group.status != AssetGroupStatus::Unloaded)
+            .map(|group| group.members.len())
+            .sum();
     }
-    /// Get the number of assets that have been loaded
+    /// Get the number of assets in every preloaded group that have been loaded
     pub fn count_loaded(&self) -> usize {
-        return self.count_loaded;
+        return self.preloaded_groups().map(|group| group.count_loaded).sum();
     }
-    /// Get the progress of loading assets as a float between 0 and 1
+    /// Get the progress of loading the preloaded groups as a float between 0 and 1, see [`Self::group_progress`] for the others
     pub fn load_progress(&self) -> f32 {
-        return self.count_loaded as f32 / self.count() as f32;
+        if self.count() == 0 {return 1.0}
+        return self.count_loaded() as f32 / self.count() as f32;
+    }
+    /// Start loading a group, does nothing if it is already loading or loaded
+    ///
+    /// A failed group is loaded again, the assets that failed are read from disk once more
+    pub fn request_group(&mut self, group: &str) {
+        let Some(group) = self.groups.get_mut(group) else {
+            warn!("requested unknown asset group \"{}\"", group);
+            return;
+        };
+        if group.status == AssetGroupStatus::Unloaded || group.status == AssetGroupStatus::Failed {
+            group.status = AssetGroupStatus::Requested;
+        }
+    }
+    /// Drop the handles of a group so bevy can free any asset nothing else holds on to
+    pub fn release_group(&mut self, group: &str) {
+        let Some(group) = self.groups.get_mut(group) else {return};
+        for name in group.members.iter() {
+            self.asset_map.remove(name);
+            self.atlas_layouts.remove(name);
+            self.using_placeholder.remove(name);
+        }
+        group.status = AssetGroupStatus::Unloaded;
+        group.count_loaded = 0;
+    }
+    pub fn group_status(&self, group: &str) -> Option<AssetGroupStatus> {
+        return self.groups.get(group).map(|group| group.status);
+    }
+    pub fn is_group_ready(&self, group: &str) -> bool {
+        return self.group_status(group) == Some(AssetGroupStatus::Ready);
+    }
+    /// Get the progress of loading a single group as a float between 0 and 1
+    pub fn group_progress(&self, group: &str) -> f32 {
+        let Some(group) = self.groups.get(group) else {return 0.0};
+        if group.members.is_empty() {return 1.0}
+        return group.count_loaded as f32 / group.members.len() as f32;
+    }
+    /// Get the names of every group
+    pub fn group_names(&self) -> Vec<String> {
+        return self.groups.keys().cloned().collect();
+    }
+    /// Get the name an asset was registered under from its id
+    pub fn name_of(&self, id: UntypedAssetId) -> Option<&str> {
+        return self.asset_map.iter()
+            .find(|(_, handle)| handle.id() == id)
+            .map(|(name, _)| name.as_str());
+    }
+    /// Get the group an asset belongs to
+    pub fn group_of(&self, name: &str) -> Option<&str> {
+        return self.groups.iter()
+            .find(|(_, group)| group.members.iter().any(|member| member == name))
+            .map(|(group_name, _)| group_name.as_str());
+    }
+    /// Forget an asset entirely, its handle is dropped and it leaves its group
+    fn remove_asset(&mut self, name: &str) {
+        self.asset_links.remove(name);
+        self.asset_types.remove(name);
+        self.optional.remove(name);
+        self.atlas_grids.remove(name);
+        self.leave_group(name);
+    }
+    /// Take an asset out of its group and drop its handle, a ready group just has one asset fewer
+    fn leave_group(&mut self, name: &str) {
+        self.asset_map.remove(name);
+        self.atlas_layouts.remove(name);
+        self.using_placeholder.remove(name);
+        for group in self.groups.values_mut() {
+            let Some(index) = group.members.iter().position(|member| member == name) else {continue};
+            group.members.remove(index);
+            if group.status == AssetGroupStatus::Ready {
+                group.count_loaded = group.members.len();
+            }
+        }
+    }
+    /// Bring this registry in line with a freshly read one, keeping every group's load status
+    ///
+    /// Names gone from the manifest are dropped. Names added or moved to a loaded group get a handle
+    /// right away, a ready group stays ready so [`AssetGroupReady`] isn't sent for it again.
+    /// Returns the handles that were replaced because an asset now points to a different file
+    fn apply_reloaded(
+        &mut self,
+        reloaded: &LoadedAssets,
+        asset_server: &AssetServer,
+        texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    ) -> Vec<(UntypedAssetId, UntypedHandle)> {
+        let removed = self.asset_links.keys()
+            .filter(|name| !reloaded.contains(name))
+            .cloned()
+            .collect::<Vec<String>>();
+        for name in removed.iter() {
+            self.remove_asset(name);
+        }
+
+        let mut swaps = Vec::new();
+        for (group_name, reloaded_group) in reloaded.groups.iter() {
+            for name in reloaded_group.members.iter() {
+                if self.contains(name) && self.group_of(name) != Some(group_name.as_str()) {
+                    self.leave_group(name);
+                    self.join_group(group_name, name);
+                }
+                let path = &reloaded.asset_links[name];
+                let asset_type = reloaded.asset_types[name];
+                let changed = self.asset_links.get(name) != Some(path)
+                    || self.asset_types.get(name).map(|current| current.type_id) != Some(asset_type.type_id);
+
+                if !self.contains(name) {
+                    self.insert_asset(group_name, name, path, asset_type);
+                } else if changed {
+                    self.asset_links.insert(name.clone(), path.clone());
+                    self.asset_types.insert(name.clone(), asset_type);
+                }
+                if reloaded.optional.contains(name) {
+                    self.optional.insert(name.clone());
+                } else {
+                    self.optional.remove(name);
+                }
+
+                let loaded = self.group_status(group_name).is_some_and(|status| status != AssetGroupStatus::Unloaded);
+                if loaded && (changed || !self.asset_map.contains_key(name)) {
+                    let handle = (asset_type.load)(asset_server, path.clone());
+                    if let Some(old_handle) = self.asset_map.insert(name.clone(), handle.clone()) {
+                        swaps.push((old_handle.id(), handle));
+                    }
+                    self.using_placeholder.remove(name);
+                }
+
+                let grid = reloaded.atlas_grids.get(name).copied();
+                if grid == self.get_atlas_grid(name) {continue}
+                match grid {
+                    Some(grid) => {
+                        self.atlas_grids.insert(name.clone(), grid);
+                        // update the layout in place so every sprite using it picks up the new grid
+                        match self.atlas_layouts.get(name) {
+                            Some(layout) => texture_atlas_layouts.insert(layout.id(), grid.to_layout()),
+                            None if loaded => {
+                                let layout = texture_atlas_layouts.add(grid.to_layout());
+                                self.atlas_layouts.insert(name.clone(), layout);
+                            },
+                            None => {},
+                        }
+                    },
+                    None => {
+                        self.atlas_grids.remove(name);
+                        self.atlas_layouts.remove(name);
+                    },
+                }
+            }
+        }
+        return swaps;
+    }
+    /// Get the name of an asset that is still loading, if any
+    pub fn currently_loading(&self) -> Option<&str> {
+        return self.currently_loading.as_deref();
+    }
+    /// Whether an asset with this name has been added, loaded or not
+    pub fn contains(&self, name: &str) -> bool {
+        return self.asset_links.contains_key(name);
     }
     /// Get an untyped handle to an asset by name
     pub fn get_untyped(&self, name: &str) -> Option<UntypedHandle> {
+        self.warn_if_unregistered(name);
         return self.asset_map.get(name).cloned();
     }
+    /// Debug builds point out names the manifest doesn't have, release builds just fail the lookup
+    fn warn_if_unregistered(&self, name: &str) {
+        if cfg!(debug_assertions) && !self.contains(name) {
+            warn!("asset \"{}\" is looked up but not registered in the manifest", name);
+        }
+    }
     /// Get a typed handle to an asset by name
     /// 
-    /// This will panic if the asset was added with Type A and you try to get it as Type B
-    pub fn get_typed<T: Asset>(&self, name: &str) -> Option<Handle<T>> {
-        return self.get_untyped(name).map(|handle| handle.typed::<T>());
+    /// Fails if the name is unknown, its group isn't loaded, or it was added as a different type
+    pub fn get_typed<T: Asset>(&self, name: &str) -> Result<Handle<T>, AssetLookupError> {
+        let Some(asset_type) = self.asset_types.get(name) else {
+            self.warn_if_unregistered(name);
+            return Err(AssetLookupError::UnknownName { name: name.to_string() });
+        };
+        if asset_type.type_id != TypeId::of::<T>() {
+            return Err(AssetLookupError::WrongType {
+                name: name.to_string(),
+                expected: std::any::type_name::<T>(),
+                actual: asset_type.type_name,
+            });
+        }
+        let Some(handle) = self.get_untyped(name) else {
+            return Err(AssetLookupError::NotLoaded { name: name.to_string() });
+        };
+        return Ok(handle.typed::<T>());
+    }
+    /// Get a typed handle, logging the error and falling back to the default handle on failure
+    pub fn get_typed_or_default<T: Asset>(&self, name: &str) -> Handle<T> {
+        return self.get_typed::<T>(name).unwrap_or_else(|error| {
+            error!("{}", error);
+            Handle::default()
+        });
+    }
+    /// Whether the asset failed to load and was swapped for a placeholder
+    pub fn is_placeholder(&self, name: &str) -> bool {
+        return self.using_placeholder.contains(name);
+    }
+    /// Get the atlas grid declared for an asset in the manifest, if any
+    pub fn get_atlas_grid(&self, name: &str) -> Option<AtlasGrid> {
+        return self.atlas_grids.get(name).copied();
+    }
+    /// Get the image of a sprite sheet together with its shared layout
+    pub fn get_atlas(&self, name: &str) -> Result<LoadedAtlas, AssetLookupError> {
+        let image = self.get_typed::<Image>(name)?;
+        let Some(layout) = self.atlas_layouts.get(name) else {
+            return Err(AssetLookupError::NotAnAtlas { name: name.to_string() });
+        };
+        return Ok(LoadedAtlas { image, layout: layout.clone() });
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetLookupError {
+    UnknownName { name: String },
+    /// The asset is registered but its group hasn't been requested
+    NotLoaded { name: String },
+    WrongType { name: String, expected: &'static str, actual: &'static str },
+    NotAnAtlas { name: String },
+}
+
+impl fmt::Display for AssetLookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetLookupError::UnknownName { name } => write!(f, "no asset named \"{}\" is registered", name),
+            AssetLookupError::NotLoaded { name } => write!(f, "asset \"{}\" is registered but its group is not loaded", name),
+            AssetLookupError::WrongType { name, expected, actual } => write!(f, "asset \"{}\" was requested as {} but was added as {}", name, expected, actual),
+            AssetLookupError::NotAnAtlas { name } => write!(f, "asset \"{}\" has no atlas grid", name),
+        }
     }
+}
+
+impl std::error::Error for AssetLookupError {}
+
+/// A sprite sheet image and the layout built from its [`AtlasGrid`]
+#[derive(Clone)]
+pub struct LoadedAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
+impl LoadedAtlas {
+    pub fn texture_atlas(&self, index: usize) -> TextureAtlas {
+        return TextureAtlas {
+            layout: self.layout.clone(),
+            index,
+        };
+    }
+}
+
+/// The folder bevy's AssetServer reads from
+pub fn asset_root() -> PathBuf {
+    return FileAssetReader::get_base_path().join("assets");
+}
+
+#[derive(Deserialize)]
+pub struct AssetManifest {
+    /// Groups loaded at startup
+    #[serde(default = "default_preload")]
+    pub preload: Vec<String>,
+    pub assets: Vec<ManifestEntry>,
+}
 
-    pub fn get_untyped_clone(&self, name: &str) -> Option<UntypedHandle> {
-        return self.get_untyped(name).map(|handle| handle.clone());
+fn default_group() -> String {
+    return DEFAULT_GROUP.to_string();
+}
+fn default_preload() -> Vec<String> {
+    return vec![default_group()];
+}
+
+impl AssetManifest {
+    pub fn read(manifest_path: &str) -> Result<Self, ManifestError> {
+        let full_path = asset_root().join(manifest_path);
+        let contents = std::fs::read_to_string(&full_path)
+            .map_err(|error| ManifestError::Io { path: full_path.clone(), error })?;
+        return ron::de::from_str::<Self>(&contents)
+            .map_err(|error| ManifestError::Parse { path: full_path, error });
     }
-    
-    pub fn get_typed_clone<T: Asset>(&self, name: &str) -> Option<Handle<T>> {
-        return self.get_typed(name).map(|handle| handle.clone());
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ManifestEntry {
+    pub name: String,
+    pub path: String,
+    pub kind: String,
+    #[serde(default = "default_group")]
+    pub group: String,
+    #[serde(default)]
+    pub atlas: Option<AtlasGrid>,
+    /// Optional assets fall back to a placeholder instead of failing the load
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Grid of equally sized tiles in a sprite sheet
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasGrid {
+    pub tile_size: (u32, u32),
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl AtlasGrid {
+    pub fn to_layout(&self) -> TextureAtlasLayout {
+        return TextureAtlasLayout::from_grid(UVec2::new(self.tile_size.0, self.tile_size.1), self.columns, self.rows, None, None);
     }
+}
 
+#[derive(Debug)]
+pub enum ManifestError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, error: ron::error::SpannedError },
+    UnknownKind { name: String, kind: String },
+    DuplicateName { name: String },
+    MissingFile { name: String, path: PathBuf },
+    AtlasOnNonImage { name: String, kind: String },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io { path, error } => write!(f, "could not read manifest {}: {}", path.display(), error),
+            ManifestError::Parse { path, error } => write!(f, "could not parse manifest {}: {}", path.display(), error),
+            ManifestError::UnknownKind { name, kind } => write!(f, "asset \"{}\" has unknown kind \"{}\" (expected image, audio or shader)", name, kind),
+            ManifestError::DuplicateName { name } => write!(f, "asset name \"{}\" is registered more than once", name),
+            ManifestError::MissingFile { name, path } => write!(f, "asset \"{}\" points to {}, which does not exist", name, path.display()),
+            ManifestError::AtlasOnNonImage { name, kind } => write!(f, "asset \"{}\" has an atlas grid but is a {}, only images can be atlases", name, kind),
+        }
+    }
 }
 
 #[derive(States, Hash, Eq, PartialEq, Clone, Debug, Default)]
 pub enum AssetLoadState {
     #[default] Loading,
     Ready,
+    /// At least one required asset in a requested group failed, see [`AssetLoadFailures`]
+    Failed,
+}
+
+/// Every required asset that failed to load and the reason it failed
+#[derive(Resource, Default, Clone)]
+pub struct AssetLoadFailures {
+    pub failed: Vec<FailedAsset>,
 }
 
-fn load (
+#[derive(Clone, Debug)]
+pub struct FailedAsset {
+    pub group: String,
+    pub name: String,
+    pub path: String,
+    pub reason: String,
+}
+
+impl AssetLoadFailures {
+    pub fn contains(&self, name: &str) -> bool {
+        return self.failed.iter().any(|failed| failed.name == name);
+    }
+}
+
+fn load_requested_groups (
     asset_server: Res<AssetServer>,
     mut loaded_assets: ResMut<LoadedAssets>,
+    mut failures: ResMut<AssetLoadFailures>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
-    for (name, path) in loaded_assets.asset_links.clone().iter() {
-        let handle: UntypedHandle = loaded_assets.load_functions[name](&asset_server, path.to_string());
-        loaded_assets.asset_map.insert(name.to_string(), handle);
+    let loaded_assets = loaded_assets.as_mut();
+    for (group_name, group) in loaded_assets.groups.iter_mut() {
+        if group.status != AssetGroupStatus::Requested {continue}
+        // the asset server keeps a failed load around, those have to be reloaded to try again
+        let retry = failures.failed.iter()
+            .filter(|failed| failed.group == *group_name)
+            .map(|failed| failed.name.clone())
+            .collect::<HashSet<String>>();
+        failures.failed.retain(|failed| failed.group != *group_name);
+        for name in group.members.iter() {
+            let path = loaded_assets.asset_links[name].clone();
+            let handle: UntypedHandle = (loaded_assets.asset_types[name].load)(&asset_server, path.clone());
+            if retry.contains(name) {
+                asset_server.reload(path);
+            }
+            loaded_assets.asset_map.insert(name.to_string(), handle);
+            if let Some(grid) = loaded_assets.atlas_grids.get(name) {
+                let layout = texture_atlas_layouts.add(grid.to_layout());
+                loaded_assets.atlas_layouts.insert(name.to_string(), layout);
+            }
+        }
+        group.status = AssetGroupStatus::Loading;
     }
 }
 
 fn verify_load (
     mut loaded_assets: ResMut<LoadedAssets>,
+    mut failures: ResMut<AssetLoadFailures>,
     asset_server: Res<AssetServer>,
-    mut next_state: ResMut<NextState<AssetLoadState>>
+    state: Res<State<AssetLoadState>>,
+    mut next_state: ResMut<NextState<AssetLoadState>>,
+    mut ready_events: EventWriter<AssetGroupReady>,
 ) {
-    let all_loaded = loaded_assets.asset_map.values().all(|handle| matches!(asset_server.get_load_state(handle.id()), Some(LoadState::Loaded)));
-    loaded_assets.count_loaded = loaded_assets.asset_map.values().filter(|handle| matches!(asset_server.get_load_state(handle.id()), Some(LoadState::Loaded))).count();
-    if all_loaded {
-        next_state.set(AssetLoadState::Ready);
+    let loaded_assets = loaded_assets.as_mut();
+    let mut all_pending: Vec<String> = Vec::new();
+    for (group_name, group) in loaded_assets.groups.iter_mut() {
+        if group.status != AssetGroupStatus::Loading {continue}
+        let mut count_loaded = 0;
+        let mut pending = 0;
+        for name in group.members.iter() {
+            if loaded_assets.using_placeholder.contains(name) {
+                count_loaded += 1;
+                continue;
+            }
+            match asset_server.get_load_state(loaded_assets.asset_map[name].id()) {
+                Some(LoadState::Loaded) => count_loaded += 1,
+                Some(LoadState::Failed(error)) => {
+                    let path = loaded_assets.asset_links[name].clone();
+                    if loaded_assets.optional.contains(name) {
+                        warn!("optional asset \"{}\" ({}) failed to load, using a placeholder: {}", name, path, error);
+                        let placeholder = (loaded_assets.asset_types[name].placeholder)();
+                        loaded_assets.asset_map.insert(name.clone(), placeholder);
+                        loaded_assets.using_placeholder.insert(name.clone());
+                        count_loaded += 1;
+                    } else if !failures.contains(name) {
+                        error!("asset \"{}\" ({}) failed to load: {}", name, path, error);
+                        failures.failed.push(FailedAsset {
+                            group: group_name.clone(),
+                            name: name.clone(),
+                            path,
+                            reason: error.to_string(),
+                        });
+                    }
+                },
+                _ => {
+                    pending += 1;
+                    all_pending.push(name.clone());
+                },
+            }
+        }
+        group.count_loaded = count_loaded;
+
+        if pending > 0 {continue}
+        if failures.failed.iter().any(|failed| failed.group == *group_name) {
+            group.status = AssetGroupStatus::Failed;
+        } else {
+            group.status = AssetGroupStatus::Ready;
+            ready_events.send(AssetGroupReady { group: group_name.clone() });
+        }
+    }
+    all_pending.sort();
+    loaded_assets.currently_loading = all_pending.first().cloned();
+
+    // ready waits for the groups loaded at startup, a group requested later that fails shows the error view too
+    let any_failed = loaded_assets.groups.values().any(|group| group.status == AssetGroupStatus::Failed);
+    match state.get() {
+        AssetLoadState::Loading => {
+            let preload_status = loaded_assets.preloaded_groups()
+                .map(|group| group.status)
+                .collect::<Vec<AssetGroupStatus>>();
+            if !preload_status.iter().all(|status| *status == AssetGroupStatus::Ready || *status == AssetGroupStatus::Failed) {return}
+            if any_failed {
+                next_state.set(AssetLoadState::Failed);
+            } else {
+                next_state.set(AssetLoadState::Ready);
+            }
+        },
+        AssetLoadState::Ready => {
+            if any_failed {
+                next_state.set(AssetLoadState::Failed);
+            }
+        },
+        AssetLoadState::Failed => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app that can hand out handles, nothing is ever found on disk
+    fn asset_app () -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Image>();
+        app.init_asset::<TextureAtlasLayout>();
+        return app;
+    }
+
+    /// A registry with every group requested and every asset handed a handle, like after loading
+    fn loaded_registry (app: &App, groups: &[(&str, &[&str])]) -> LoadedAssets {
+        let mut loaded_assets = manifest_registry(groups);
+        let asset_server = app.world().resource::<AssetServer>();
+        for group in loaded_assets.groups.values_mut() {
+            group.status = AssetGroupStatus::Ready;
+            group.count_loaded = group.members.len();
+            for name in group.members.iter() {
+                let handle = load_function::<Image>(asset_server, format!("{}.png", name));
+                loaded_assets.asset_map.insert(name.clone(), handle);
+            }
+        }
+        return loaded_assets;
+    }
+
+    /// A registry as read from a manifest, nothing requested
+    fn manifest_registry (groups: &[(&str, &[&str])]) -> LoadedAssets {
+        let mut loaded_assets = LoadedAssets::new();
+        for (group, names) in groups {
+            for name in names.iter() {
+                loaded_assets.add_asset::<Image>(group, name, &format!("{}.png", name));
+            }
+        }
+        return loaded_assets;
+    }
+
+    fn apply (app: &mut App, loaded_assets: &mut LoadedAssets, reloaded: &LoadedAssets) {
+        app.world_mut().resource_scope(|world, mut texture_atlas_layouts: Mut<Assets<TextureAtlasLayout>>| {
+            loaded_assets.apply_reloaded(reloaded, world.resource::<AssetServer>(), &mut texture_atlas_layouts);
+        });
+    }
+
+    #[test]
+    fn reload_drops_names_removed_from_the_manifest () {
+        let mut app = asset_app();
+        let mut loaded_assets = loaded_registry(&app, &[("common", &["sand", "reef"])]);
+        apply(&mut app, &mut loaded_assets, &manifest_registry(&[("common", &["sand"])]));
+
+        assert!(!loaded_assets.contains("reef"));
+        assert!(loaded_assets.get_untyped("reef").is_none());
+        assert_eq!(loaded_assets.group_of("reef"), None);
+        assert_eq!(loaded_assets.group_status("common"), Some(AssetGroupStatus::Ready));
+        assert_eq!(loaded_assets.group_progress("common"), 1.0);
+    }
+
+    #[test]
+    fn reload_moves_names_to_their_new_group () {
+        let mut app = asset_app();
+        let mut loaded_assets = loaded_registry(&app, &[("common", &["sand", "reef"]), ("water_scene", &["kelp"]), ("boss", &["boss_music"])]);
+        loaded_assets.release_group("boss");
+        apply(&mut app, &mut loaded_assets, &manifest_registry(&[("common", &["sand"]), ("water_scene", &["kelp", "reef"]), ("boss", &["boss_music"])]));
+
+        // a ready group that gains an asset loads it and stays ready
+        assert_eq!(loaded_assets.group_of("reef"), Some("water_scene"));
+        assert_eq!(loaded_assets.group_status("water_scene"), Some(AssetGroupStatus::Ready));
+        assert_eq!(loaded_assets.group_progress("water_scene"), 1.0);
+        assert!(loaded_assets.get_untyped("reef").is_some());
+        assert_eq!(loaded_assets.group_status("common"), Some(AssetGroupStatus::Ready));
+
+        // moving into a group that isn't loaded drops the handle
+        apply(&mut app, &mut loaded_assets, &manifest_registry(&[("common", &["sand"]), ("water_scene", &["kelp"]), ("boss", &["boss_music", "reef"])]));
+        assert_eq!(loaded_assets.group_of("reef"), Some("boss"));
+        assert!(loaded_assets.get_untyped("reef").is_none());
+    }
+
+    #[test]
+    fn progress_only_counts_preloaded_groups () {
+        let app = asset_app();
+        let mut loaded_assets = loaded_registry(&app, &[("common", &["sand", "reef"]), ("water_scene", &["kelp", "coral"])]);
+        loaded_assets.groups.get_mut("water_scene").unwrap().count_loaded = 0;
+        loaded_assets.groups.get_mut("water_scene").unwrap().status = AssetGroupStatus::Loading;
+
+        assert_eq!(loaded_assets.count(), 2);
+        assert_eq!(loaded_assets.count_loaded(), 2);
+        assert_eq!(loaded_assets.load_progress(), 1.0);
+        assert_eq!(loaded_assets.group_progress("water_scene"), 0.0);
+    }
+
+    #[test]
+    fn failed_scene_group_shows_the_error_view () {
+        let mut app = asset_app();
+        app.add_plugins(bevy::state::app::StatesPlugin);
+        let mut plugin = AssetLoadPlugin::new();
+        plugin.add_group_asset::<Image>("water_scene", "kelp", "missing/kelp.png");
+        app.add_plugins(plugin);
+        app.world_mut().resource_mut::<LoadedAssets>().request_group("water_scene");
+
+        for _ in 0..100 {
+            app.update();
+            if *app.world().resource::<State<AssetLoadState>>().get() == AssetLoadState::Failed {break}
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(*app.world().resource::<State<AssetLoadState>>().get(), AssetLoadState::Failed);
+        assert_eq!(app.world().resource::<LoadedAssets>().group_status("water_scene"), Some(AssetGroupStatus::Failed));
+        assert!(app.world().resource::<AssetLoadFailures>().contains("kelp"));
+
+        // requesting it again tries the failed asset once more
+        app.world_mut().resource_mut::<LoadedAssets>().request_group("water_scene");
+        app.update();
+        assert_eq!(app.world().resource::<LoadedAssets>().group_status("water_scene"), Some(AssetGroupStatus::Loading));
+        assert!(!app.world().resource::<AssetLoadFailures>().contains("kelp"));
+        for _ in 0..100 {
+            app.update();
+            if app.world().resource::<LoadedAssets>().group_status("water_scene") == Some(AssetGroupStatus::Failed) {break}
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(app.world().resource::<LoadedAssets>().group_status("water_scene"), Some(AssetGroupStatus::Failed));
+        assert_eq!(app.world().resource::<AssetLoadFailures>().failed.len(), 1);
+    }
+
+    /// Ready events seen so far, counted by [`count_ready_events`]
+    #[derive(Resource, Default)]
+    struct ReadyEvents(Vec<String>);
+
+    fn count_ready_events (mut events: EventReader<AssetGroupReady>, mut seen: ResMut<ReadyEvents>) {
+        seen.0.extend(events.read().map(|event| event.group.clone()));
+    }
+
+    #[test]
+    fn reload_into_a_ready_group_does_not_send_ready_again () {
+        let mut app = asset_app();
+        app.add_plugins(bevy::state::app::StatesPlugin);
+        app.init_state::<AssetLoadState>();
+        app.init_resource::<AssetLoadFailures>();
+        app.init_resource::<ReadyEvents>();
+        app.add_event::<AssetGroupReady>();
+        app.add_systems(Update, (verify_load, count_ready_events).chain());
+        let loaded_assets = loaded_registry(&app, &[("common", &["sand", "reef"]), ("water_scene", &["kelp"])]);
+        app.insert_resource(loaded_assets);
+        app.world_mut().send_event(AssetGroupReady { group: "water_scene".to_string() });
+        app.update();
+
+        // one name moves into the ready group and one is new to it
+        let reloaded = manifest_registry(&[("common", &["sand"]), ("water_scene", &["kelp", "reef", "coral"])]);
+        app.world_mut().resource_scope(|world, mut loaded_assets: Mut<LoadedAssets>| {
+            world.resource_scope(|world, mut texture_atlas_layouts: Mut<Assets<TextureAtlasLayout>>| {
+                loaded_assets.apply_reloaded(&reloaded, world.resource::<AssetServer>(), &mut texture_atlas_layouts);
+            });
+        });
+        for _ in 0..5 {
+            app.update();
+        }
+
+        assert_eq!(app.world().resource::<ReadyEvents>().0, vec!["water_scene".to_string()]);
+        let loaded_assets = app.world().resource::<LoadedAssets>();
+        assert_eq!(loaded_assets.group_status("water_scene"), Some(AssetGroupStatus::Ready));
+        assert_eq!(loaded_assets.count_loaded(), 1);
+        assert_eq!(loaded_assets.group_progress("water_scene"), 1.0);
+        // a name the manifest doesn't have, through a variable so the asset audit doesn't flag it
+        let added = "coral";
+        assert!(loaded_assets.get_untyped("reef").is_some());
+        assert!(loaded_assets.get_untyped(added).is_some());
+    }
+
+    /// Every error found in a manifest, as they would be reported
+    fn manifest_errors (contents: &str) -> Vec<String> {
+        let manifest = ron::de::from_str::<AssetManifest>(contents).expect("the test manifest should parse");
+        return match AssetLoadPlugin::from_parsed_manifest(&manifest) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.iter().map(|error| error.to_string()).collect(),
+        };
+    }
+
+    #[test]
+    fn valid_manifest_has_no_errors () {
+        assert!(manifest_errors(r#"(assets: [
+            (name: "sand", path: "platforms/sand.png", kind: "image", group: "water_scene"),
+            (name: "squid_map", path: "squid/squid_map3.png", kind: "image", atlas: Some((tile_size: (32, 32), columns: 4, rows: 4))),
+        ])"#).is_empty());
+    }
+
+    #[test]
+    fn duplicate_name_is_reported () {
+        assert_eq!(manifest_errors(r#"(assets: [
+            (name: "sand", path: "platforms/sand.png", kind: "image"),
+            (name: "sand", path: "walls/walls.png", kind: "image"),
+        ])"#), vec!["asset name \"sand\" is registered more than once"]);
+    }
+
+    #[test]
+    fn unknown_kind_is_reported () {
+        assert_eq!(manifest_errors(r#"(assets: [
+            (name: "sand", path: "platforms/sand.png", kind: "texture"),
+        ])"#), vec!["asset \"sand\" has unknown kind \"texture\" (expected image, audio or shader)"]);
+    }
+
+    #[test]
+    fn missing_file_is_reported () {
+        let path = asset_root().join("platforms/gravel.png");
+        assert_eq!(manifest_errors(r#"(assets: [
+            (name: "gravel", path: "platforms/gravel.png", kind: "image"),
+        ])"#), vec![format!("asset \"gravel\" points to {}, which does not exist", path.display())]);
+    }
+
+    #[test]
+    fn atlas_on_non_image_is_reported_and_not_registered () {
+        let manifest = ron::de::from_str::<AssetManifest>(r#"(assets: [
+            (name: "sand", path: "platforms/sand.png", kind: "audio", atlas: Some((tile_size: (8, 8), columns: 2, rows: 2))),
+        ])"#).unwrap();
+        let mut plugin = AssetLoadPlugin::new();
+        let error = plugin.add_manifest_entry(&manifest.assets[0]).unwrap_err();
+        assert_eq!(error.to_string(), "asset \"sand\" has an atlas grid but is a audio, only images can be atlases");
+        assert!(!plugin.0.contains("sand"));
+        assert_eq!(plugin.0.group_of("sand"), None);
+    }
+
+    #[test]
+    fn every_error_is_collected_in_one_pass () {
+        let path = asset_root().join("nowhere.png");
+        assert_eq!(manifest_errors(r#"(assets: [
+            (name: "sand", path: "platforms/sand.png", kind: "image"),
+            (name: "sand", path: "platforms/sand.png", kind: "image"),
+            (name: "music", path: "platforms/sand.png", kind: "song"),
+            (name: "nowhere", path: "nowhere.png", kind: "image"),
+            (name: "walls", path: "walls/walls.png", kind: "shader", atlas: Some((tile_size: (8, 8), columns: 2, rows: 2))),
+            (name: "squid", path: "squid/squiddy_flat.png", kind: "image"),
+        ])"#), vec![
+            "asset name \"sand\" is registered more than once".to_string(),
+            "asset \"music\" has unknown kind \"song\" (expected image, audio or shader)".to_string(),
+            format!("asset \"nowhere\" points to {}, which does not exist", path.display()),
+            "asset \"walls\" has an atlas grid but is a shader, only images can be atlases".to_string(),
+        ]);
     }
 }
@@ -0,0 +1,15 @@
+pub mod flex_load;
+pub mod asset_registry;
+pub mod loading_screen;
+
+pub mod player_character;
+
+pub mod objects;
+
+pub mod scenes;
+
+pub mod enemies;
+
+pub static BACKGROUND_Z: f32 = -100.0;
+pub static PLATFORM_Z: f32 = -50.0;
+pub static PLAYER_Z: f32 = 10.0;
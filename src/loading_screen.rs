@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use crate::flex_load::*;
+
+/// Shows load progress while [`AssetLoadState::Loading`] and the failed assets if loading fails
+pub struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AssetLoadState::Loading), spawn_loading_screen);
+        app.add_systems(Update, update_loading_screen.run_if(in_state(AssetLoadState::Loading)));
+        app.add_systems(OnEnter(AssetLoadState::Ready), despawn_loading_screen);
+        app.add_systems(OnEnter(AssetLoadState::Failed), show_load_errors);
+    }
+}
+
+/// Everything spawned by the loading screen, including its camera
+#[derive(Component)]
+struct LoadingScreen;
+
+/// The root ui node, replaced by the error view when loading fails
+#[derive(Component)]
+struct LoadingScreenRoot;
+
+#[derive(Component)]
+struct ProgressBarFill;
+
+#[derive(Component)]
+struct CurrentAssetText;
+
+fn spawn_loading_screen (
+    mut commands: Commands,
+) {
+    commands.spawn((Camera2d, LoadingScreen));
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK),
+        LoadingScreen,
+        LoadingScreenRoot,
+    )).with_children(|parent| {
+        parent.spawn((
+            Text("Loading".to_string()),
+            TextColor(Color::WHITE),
+        ));
+        parent.spawn((
+            Node {
+                width: Val::Px(400.0),
+                height: Val::Px(20.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        )).with_children(|bar| {
+            bar.spawn((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.3, 0.5, 0.9)),
+                ProgressBarFill,
+            ));
+        });
+        parent.spawn((
+            Text("".to_string()),
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+            CurrentAssetText,
+        ));
+    });
+}
+
+fn update_loading_screen (
+    loaded_assets: Res<LoadedAssets>,
+    mut fill_query: Query<&mut Node, With<ProgressBarFill>>,
+    mut text_query: Query<&mut Text, With<CurrentAssetText>>,
+) {
+    for mut fill in fill_query.iter_mut() {
+        fill.width = Val::Percent(loaded_assets.load_progress() * 100.0);
+    }
+    for mut text in text_query.iter_mut() {
+        text.0 = match loaded_assets.currently_loading() {
+            Some(name) => format!("{} ({}/{})", name, loaded_assets.count_loaded(), loaded_assets.count()),
+            None => "".to_string(),
+        };
+    }
+}
+
+fn despawn_loading_screen (
+    mut commands: Commands,
+    query: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn show_load_errors (
+    mut commands: Commands,
+    failures: Res<AssetLoadFailures>,
+    root_query: Query<Entity, With<LoadingScreenRoot>>,
+) {
+    for entity in root_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(6.0),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK),
+        LoadingScreen,
+        LoadingScreenRoot,
+    )).with_children(|parent| {
+        parent.spawn((
+            Text(format!("Failed to load {} asset(s)", failures.failed.len())),
+            TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        ));
+        for failed in failures.failed.iter() {
+            parent.spawn((
+                Text(format!("{} ({}): {}", failed.name, failed.path, failed.reason)),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        }
+    });
+}
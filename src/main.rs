@@ -2,40 +2,32 @@ use bevy::prelude::*;
 use bevy::render::texture::ImagePlugin;
 use bevy_rapier2d::prelude::*;
 
-pub mod flex_load;
-pub mod asset_registry;
-use asset_registry::*;
-
-pub mod player_character;
-use player_character::player::*;
-
-pub mod objects;
-use objects::knife_holder::*;
-
-pub mod scenes;
-use rat::RatPlugin;
-use scenes::background::*;
-use scenes::*;
-use scenes::water_scene::*;
-use layout::ComplexLayoutPlugin;
-
-
-pub mod enemies;
-use enemies::rat::*;
-
-static BACKGROUND_Z: f32 = -100.0;
-static PLATFORM_Z: f32 = -50.0;
-static PLAYER_Z: f32 = 10.0;
+use sdk_reborn::asset_registry::*;
+use sdk_reborn::loading_screen::*;
+use sdk_reborn::player_character::player::*;
+use sdk_reborn::player_character::replay::*;
+use sdk_reborn::scenes::platform::*;
+use sdk_reborn::scenes::water_scene::*;
+use sdk_reborn::enemies::rat::rat::RatPlugin;
 
 fn main() {
     let mut app = App::new();
+    let default_plugins = DefaultPlugins.set(ImagePlugin::default_nearest());
+    #[cfg(feature = "hot_reload")]
+    let default_plugins = default_plugins.set(AssetPlugin {
+        watch_for_changes_override: Some(true),
+        ..default()
+    });
     app.add_plugins((
-        DefaultPlugins.set(ImagePlugin::default_nearest()), 
+        default_plugins, 
         LoadedAssetsPlugin, // see asset_registry.rs
-        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(64.0), 
+        LoadingScreenPlugin,
+        RapierPhysicsPlugin::<OneWayPlatformHooks>::pixels_per_meter(64.0), 
+        PlatformPlugin,
         // RapierDebugRenderPlugin::default(), // physics colliders debug rendering
         // ComplexLayoutPlugin,
         SquidPlugin,
+        InputReplayPlugin::from_args(), // --record <file> or --replay <file>
         WaterScenePlugin,
         RatPlugin,
     ));
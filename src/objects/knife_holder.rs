@@ -77,7 +77,7 @@ fn tick_knife_holders (
                         knife_struct.state = KnifeState::Shooting;
                         let old_position = knife_transform.translation;
                         commands.entity(entity).with_children(|parent| {
-                            spawn_creeping_knife(parent, loaded.get_typed("small_knife").unwrap(), old_position, knife_struct.index);
+                            spawn_creeping_knife(parent, loaded.get_typed_or_default("small_knife"), old_position, knife_struct.index);
                         });
                     }
                 }
@@ -128,7 +128,7 @@ pub fn spawn_knife_holder (
     commands.spawn( (
         knife_holder.clone(),
         Sprite {
-            image: loaded.get_typed::<Image>("knife_holder_base").unwrap(),
+            image: loaded.get_typed_or_default::<Image>("knife_holder_base"),
             custom_size: Some(Vec2::new(64.0,64.0)),
             ..default()
         },
@@ -139,7 +139,7 @@ pub fn spawn_knife_holder (
         //knife holder mask 0
         parent.spawn( ( 
             Sprite {
-                image: loaded.get_typed::<Image>("knife_holder_mask_0").unwrap(),
+                image: loaded.get_typed_or_default::<Image>("knife_holder_mask_0"),
                 custom_size: Some(Vec2::new(64.0, 64.0)),
                 ..default()
             },
@@ -148,20 +148,20 @@ pub fn spawn_knife_holder (
         //knife holder mask 1
         parent.spawn( ( 
             Sprite {
-                image: loaded.get_typed::<Image>("knife_holder_mask_1").unwrap(),
+                image: loaded.get_typed_or_default::<Image>("knife_holder_mask_1"),
                 custom_size: Some(Vec2::new(64.0, 64.0)),
                 ..default()
             },
             Transform::from_translation(Vec3::new(0.0,0.0,0.4)),
         ));
         //back knives
-        spawn_ready_knife(parent, loaded.get_typed("small_knife").unwrap(), Vec3::new(-25.0, 10.0, 0.1), 0);
-        spawn_ready_knife(parent, loaded.get_typed("small_knife").unwrap(), Vec3::new(-25.0, -10.0, 0.1), 4);
+        spawn_ready_knife(parent, loaded.get_typed_or_default("small_knife"), Vec3::new(-25.0, 10.0, 0.1), 0);
+        spawn_ready_knife(parent, loaded.get_typed_or_default("small_knife"), Vec3::new(-25.0, -10.0, 0.1), 4);
 
         //front knives
-        spawn_ready_knife(parent, loaded.get_typed("small_knife").unwrap(), Vec3::new(-17.0, 13.0, 0.3), 3);
-        spawn_ready_knife(parent, loaded.get_typed("small_knife").unwrap(), Vec3::new(-17.0, -1.0, 0.3), 2);
-        spawn_ready_knife(parent, loaded.get_typed("small_knife").unwrap(), Vec3::new(-17.0, -13.0, 0.3), 1);
+        spawn_ready_knife(parent, loaded.get_typed_or_default("small_knife"), Vec3::new(-17.0, 13.0, 0.3), 3);
+        spawn_ready_knife(parent, loaded.get_typed_or_default("small_knife"), Vec3::new(-17.0, -1.0, 0.3), 2);
+        spawn_ready_knife(parent, loaded.get_typed_or_default("small_knife"), Vec3::new(-17.0, -13.0, 0.3), 1);
 
         if knife_holder.debug {
             parent.spawn((
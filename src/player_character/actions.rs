@@ -0,0 +1,383 @@
+use bevy::prelude::*;
+use bevy::input::InputSystem;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::asset::io::file::FileAssetReader;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputBindings::load());
+        app.init_resource::<ActionState>();
+        app.init_resource::<Rebinding>();
+        app.add_systems(PreUpdate, (log_gamepad_connections, update_action_state, capture_rebind).chain().in_set(ReadDevices).after(InputSystem));
+    }
+}
+
+/// Systems that turn keyboard and gamepad input into [`ActionState`], skipped while a recording is replayed
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadDevices;
+
+/// Everything the player can do, systems read these instead of raw keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlayerAction {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    Dive,
+    Jump,
+    Dash,
+    /// Hold to charge, release to launch
+    Charge,
+    /// Fire the tentacle, hold to stay latched
+    Grab,
+    /// Radial attack, leaves the squid dizzy
+    Spin,
+    /// Leave a cloud of ink enemies can't see through
+    InkCloud,
+}
+
+impl PlayerAction {
+    pub const ALL: [PlayerAction; 10] = [
+        PlayerAction::MoveLeft,
+        PlayerAction::MoveRight,
+        PlayerAction::MoveUp,
+        PlayerAction::Dive,
+        PlayerAction::Jump,
+        PlayerAction::Dash,
+        PlayerAction::Charge,
+        PlayerAction::Grab,
+        PlayerAction::Spin,
+        PlayerAction::InkCloud,
+    ];
+}
+
+/// Bindings file, relative to the folder the game runs from
+pub const BINDINGS_PATH: &str = "config/bindings.ron";
+
+/// Written at the top of the bindings file on every save, any other comments in the file are lost
+const BINDINGS_HEADER: &str = "\
+// Key bindings for the squid, read at startup
+// This file is machine-written: rebinding a key in game rewrites it and only keeps this header
+// Key names are bevy KeyCodes, e.g. KeyA, ArrowLeft, Space, ShiftLeft
+// Button names are bevy GamepadButtons, e.g. South, West, DPadLeft, RightTrigger
+";
+
+fn bindings_file() -> PathBuf {
+    return FileAssetReader::get_base_path().join(BINDINGS_PATH);
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub keys: HashMap<PlayerAction, Vec<KeyCode>>,
+    #[serde(default = "default_buttons")]
+    pub buttons: HashMap<PlayerAction, Vec<GamepadButton>>,
+    /// Stick input shorter than this is ignored, between 0 and 1
+    #[serde(default = "default_stick_deadzone")]
+    pub stick_deadzone: f32,
+}
+
+fn default_buttons() -> HashMap<PlayerAction, Vec<GamepadButton>> {
+    return HashMap::from([
+        (PlayerAction::MoveLeft, vec![GamepadButton::DPadLeft]),
+        (PlayerAction::MoveRight, vec![GamepadButton::DPadRight]),
+        (PlayerAction::MoveUp, vec![GamepadButton::DPadUp]),
+        (PlayerAction::Dive, vec![GamepadButton::DPadDown]),
+        (PlayerAction::Jump, vec![GamepadButton::South]),
+        (PlayerAction::Dash, vec![GamepadButton::West, GamepadButton::RightTrigger]),
+        (PlayerAction::Charge, vec![GamepadButton::East]),
+        (PlayerAction::Grab, vec![GamepadButton::North]),
+        (PlayerAction::Spin, vec![GamepadButton::LeftTrigger]),
+        (PlayerAction::InkCloud, vec![GamepadButton::RightThumb]),
+    ]);
+}
+fn default_stick_deadzone() -> f32 {
+    return 0.2;
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            keys: HashMap::from([
+                (PlayerAction::MoveLeft, vec![KeyCode::KeyA]),
+                (PlayerAction::MoveRight, vec![KeyCode::KeyD]),
+                (PlayerAction::MoveUp, vec![KeyCode::KeyW]),
+                (PlayerAction::Dive, vec![KeyCode::KeyS]),
+                (PlayerAction::Jump, vec![KeyCode::Space]),
+                (PlayerAction::Dash, vec![KeyCode::ShiftLeft]),
+                (PlayerAction::Charge, vec![KeyCode::KeyE]),
+                (PlayerAction::Grab, vec![KeyCode::KeyF]),
+                (PlayerAction::Spin, vec![KeyCode::KeyQ]),
+                (PlayerAction::InkCloud, vec![KeyCode::KeyC]),
+            ]),
+            buttons: default_buttons(),
+            stick_deadzone: default_stick_deadzone(),
+        }
+    }
+}
+
+impl InputBindings {
+    /// Read the bindings file, falling back to the defaults if it is missing or invalid
+    pub fn load() -> Self {
+        let path = bindings_file();
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("could not read key bindings {}, using defaults: {}", path.display(), error);
+                return Self::default();
+            }
+        };
+        match ron::de::from_str::<Self>(&contents) {
+            Ok(mut bindings) => {
+                bindings.fill_missing();
+                return bindings;
+            },
+            Err(error) => {
+                error!("could not parse key bindings {}, using defaults: {}", path.display(), error);
+                return Self::default();
+            }
+        }
+    }
+    pub fn save(&self) -> Result<(), String> {
+        return std::fs::write(bindings_file(), self.file_contents()?).map_err(|error| error.to_string());
+    }
+    /// What `save` writes, the header followed by every binding
+    fn file_contents(&self) -> Result<String, String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())?;
+        return Ok(format!("{}{}\n", BINDINGS_HEADER, contents));
+    }
+    pub fn keys_for(&self, action: PlayerAction) -> &[KeyCode] {
+        return self.keys.get(&action).map(|keys| keys.as_slice()).unwrap_or(&[]);
+    }
+    /// Make `key` the only binding for `action`, and take it away from any other action
+    pub fn rebind(&mut self, action: PlayerAction, key: KeyCode) {
+        for keys in self.keys.values_mut() {
+            keys.retain(|bound| *bound != key);
+        }
+        self.keys.insert(action, vec![key]);
+    }
+    pub fn buttons_for(&self, action: PlayerAction) -> &[GamepadButton] {
+        return self.buttons.get(&action).map(|buttons| buttons.as_slice()).unwrap_or(&[]);
+    }
+    /// Make `button` the only gamepad binding for `action`, and take it away from any other action
+    pub fn rebind_button(&mut self, action: PlayerAction, button: GamepadButton) {
+        for buttons in self.buttons.values_mut() {
+            buttons.retain(|bound| *bound != button);
+        }
+        self.buttons.insert(action, vec![button]);
+    }
+    /// Scale a stick so the deadzone reads as zero and the edge as one
+    pub fn apply_deadzone(&self, stick: Vec2) -> Vec2 {
+        let length = stick.length();
+        if length <= self.stick_deadzone {return Vec2::ZERO}
+        let scaled = ((length - self.stick_deadzone) / (1.0 - self.stick_deadzone)).min(1.0);
+        return stick / length * scaled;
+    }
+    /// Give actions added since the file was written their default bindings
+    fn fill_missing(&mut self) {
+        let defaults = Self::default();
+        for action in PlayerAction::ALL {
+            self.keys.entry(action).or_insert_with(|| defaults.keys_for(action).to_vec());
+            self.buttons.entry(action).or_insert_with(|| defaults.buttons_for(action).to_vec());
+        }
+    }
+}
+
+/// Which actions are held this frame, filled from the bindings before Update runs
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<PlayerAction>,
+    just_pressed: HashSet<PlayerAction>,
+    just_released: HashSet<PlayerAction>,
+    move_axis: Vec2,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: PlayerAction) -> bool {
+        return self.pressed.contains(&action);
+    }
+    pub fn just_pressed(&self, action: PlayerAction) -> bool {
+        return self.just_pressed.contains(&action);
+    }
+    pub fn just_released(&self, action: PlayerAction) -> bool {
+        return self.just_released.contains(&action);
+    }
+    /// Set the held state of an action, press and release edges are worked out from the previous frame
+    pub fn set(&mut self, action: PlayerAction, held: bool) {
+        let was_held = self.pressed.contains(&action);
+        if held && !was_held {
+            self.pressed.insert(action);
+            self.just_pressed.insert(action);
+        } else if !held && was_held {
+            self.pressed.remove(&action);
+            self.just_released.insert(action);
+        }
+    }
+    /// Movement direction with each component between -1 and 1, analog when a stick is in use
+    pub fn move_axis(&self) -> Vec2 {
+        return self.move_axis;
+    }
+    pub fn set_move_axis(&mut self, axis: Vec2) {
+        self.move_axis = axis.clamp(Vec2::NEG_ONE, Vec2::ONE);
+    }
+    /// Forget last frame's press and release edges
+    pub fn clear_edges(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+}
+
+/// Set `waiting_for` to rebind the next key or gamepad button pressed to that action, Escape cancels
+#[derive(Resource, Default)]
+pub struct Rebinding {
+    pub waiting_for: Option<PlayerAction>,
+}
+
+impl Rebinding {
+    pub fn start(&mut self, action: PlayerAction) {
+        self.waiting_for = Some(action);
+    }
+}
+
+fn capture_rebind (
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+) {
+    let Some(action) = rebinding.waiting_for else {return};
+    if let Some(key) = keys.get_just_pressed().next() {
+        rebinding.waiting_for = None;
+        // cancelled, the file is left alone
+        if *key == KeyCode::Escape {return}
+        bindings.rebind(action, *key);
+    } else if let Some(button) = gamepads.iter().find_map(|gamepad| gamepad.get_just_pressed().next().copied()) {
+        rebinding.waiting_for = None;
+        bindings.rebind_button(action, button);
+    } else {
+        return;
+    }
+    if let Err(error) = bindings.save() {
+        error!("could not save key bindings: {}", error);
+    }
+}
+
+fn update_action_state (
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    mut action_state: ResMut<ActionState>,
+) {
+    action_state.clear_edges();
+    for action in PlayerAction::ALL {
+        let key_held = keys.any_pressed(bindings.keys_for(action).iter().copied());
+        let button_held = gamepads.iter().any(|gamepad| gamepad.any_pressed(bindings.buttons_for(action).iter().copied()));
+        // the input being bound shouldn't also trigger whatever it was bound to before
+        action_state.set(action, rebinding.waiting_for.is_none() && (key_held || button_held));
+    }
+
+    // the strongest stick wins, otherwise fall back to the digital directions
+    let stick = gamepads.iter()
+        .map(|gamepad| bindings.apply_deadzone(gamepad.left_stick()))
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or(Vec2::ZERO);
+    if stick != Vec2::ZERO {
+        action_state.set_move_axis(stick);
+    } else {
+        let mut axis = Vec2::ZERO;
+        if action_state.pressed(PlayerAction::MoveLeft) {axis.x -= 1.0}
+        if action_state.pressed(PlayerAction::MoveRight) {axis.x += 1.0}
+        if action_state.pressed(PlayerAction::MoveUp) {axis.y += 1.0}
+        if action_state.pressed(PlayerAction::Dive) {axis.y -= 1.0}
+        action_state.set_move_axis(axis);
+    }
+}
+
+/// Gamepads can come and go at any time, every connected one is read each frame
+fn log_gamepad_connections (
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+) {
+    for event in connection_events.read() {
+        match &event.connection {
+            GamepadConnection::Connected { name, .. } => info!("gamepad connected: {}", name),
+            GamepadConnection::Disconnected => info!("gamepad disconnected"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_file_keeps_its_header_and_parses_back () {
+        let mut bindings = InputBindings::default();
+        bindings.rebind(PlayerAction::Jump, KeyCode::KeyK);
+        let contents = bindings.file_contents().unwrap();
+        assert!(contents.starts_with(BINDINGS_HEADER));
+        let parsed = ron::de::from_str::<InputBindings>(&contents).unwrap();
+        assert_eq!(parsed.keys_for(PlayerAction::Jump), &[KeyCode::KeyK]);
+    }
+
+    #[test]
+    fn escape_cancels_a_rebind () {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.insert_resource(InputBindings::default());
+        app.insert_resource(Rebinding {waiting_for: Some(PlayerAction::Jump)});
+        app.add_systems(Update, capture_rebind);
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Escape);
+        app.update();
+        assert!(app.world().resource::<Rebinding>().waiting_for.is_none());
+        assert_eq!(app.world().resource::<InputBindings>().keys_for(PlayerAction::Jump), &[KeyCode::Space]);
+    }
+
+    #[test]
+    fn rebind_moves_a_key_to_the_new_action () {
+        let mut bindings = InputBindings::default();
+        bindings.rebind(PlayerAction::Jump, KeyCode::KeyA);
+        assert_eq!(bindings.keys_for(PlayerAction::Jump), &[KeyCode::KeyA]);
+        assert!(bindings.keys_for(PlayerAction::MoveLeft).is_empty());
+        assert_eq!(bindings.keys_for(PlayerAction::MoveRight), &[KeyCode::KeyD]);
+    }
+
+    #[test]
+    fn rebind_button_moves_a_button_to_the_new_action () {
+        let mut bindings = InputBindings::default();
+        bindings.rebind_button(PlayerAction::Grab, GamepadButton::West);
+        assert_eq!(bindings.buttons_for(PlayerAction::Grab), &[GamepadButton::West]);
+        assert_eq!(bindings.buttons_for(PlayerAction::Dash), &[GamepadButton::RightTrigger]);
+    }
+
+    #[test]
+    fn missing_actions_get_their_defaults () {
+        let mut bindings = InputBindings::default();
+        bindings.keys.remove(&PlayerAction::InkCloud);
+        bindings.buttons.remove(&PlayerAction::InkCloud);
+        bindings.fill_missing();
+        assert_eq!(bindings.keys_for(PlayerAction::InkCloud), &[KeyCode::KeyC]);
+        assert_eq!(bindings.buttons_for(PlayerAction::InkCloud), &[GamepadButton::RightThumb]);
+    }
+
+    #[test]
+    fn sticks_inside_the_deadzone_read_as_zero () {
+        let bindings = InputBindings::default();
+        assert_eq!(bindings.apply_deadzone(Vec2::new(0.1, 0.1)), Vec2::ZERO);
+        assert_eq!(bindings.apply_deadzone(Vec2::new(0.0, -0.2)), Vec2::ZERO);
+    }
+
+    #[test]
+    fn sticks_outside_the_deadzone_are_rescaled () {
+        let bindings = InputBindings::default();
+        assert!((bindings.apply_deadzone(Vec2::new(0.6, 0.0)) - Vec2::new(0.5, 0.0)).length() < 1e-6);
+        assert!((bindings.apply_deadzone(Vec2::new(0.0, -1.0)) - Vec2::NEG_Y).length() < 1e-6);
+        // direction is kept, length is capped at one
+        let diagonal = bindings.apply_deadzone(Vec2::new(1.0, 1.0));
+        assert!((diagonal - Vec2::new(1.0, 1.0).normalize()).length() < 1e-6);
+    }
+}
@@ -1,78 +1,128 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use crate::{player_character::*, scenes::*};
 use rand::*;
 pub struct BaseMovementPlugin;
 
 impl Plugin for BaseMovementPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(DashTimer::new(0.5));
-        app.add_systems(Update, ((control_squid, manage_dash).chain(), tick_dash_timer, manage_feet));
+        app.insert_resource(DashTimer::new(MovementPreset::default().dash_duration));
+        app.add_systems(Update, ((drop_through_platforms, control_squid, manage_dash.after(detect_combos)).chain().in_set(MovementSystems), tick_dash_timer, manage_feet.before(MovementSystems)));
     }
 }
 
-fn control_squid (
-    mut player_query: Query<(&mut Player, &mut Velocity, &mut GravityScale, &mut PlayerAnimation, &mut Transform)>,
+/// Steepest ground the squid runs along, as the up component of the ground normal
+const WALKABLE_NORMAL_Y: f32 = 0.5;
+
+/// How far the squid's bottom may sink into a passthrough platform and still stand on it
+const PASSTHROUGH_LANDING_TOLERANCE: f32 = 4.0;
+
+/// Walking, jumping and dashing, systems that override the squid's velocity run after these
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MovementSystems;
+
+pub(crate) fn control_squid (
+    mut player_query: Query<(&mut Player, &mut Velocity, &mut GravityScale, &mut PlayerAnimation, &mut Transform, &mut InkReserve, Has<Dizzy>)>,
+    feet_query: Query<&FeetSensor>,
+    slick_query: Query<(), With<SlickPlatform>>,
     mut splotch_registry: ResMut<SplotchRegistry>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     dash_timer: Res<DashTimer>,
+    movement_config: Res<MovementConfig>,
     time: Res<Time>,
 ) {
     if player_query.iter().count() == 0 {return}
-    let (mut player_struct, mut velocity, mut gravity, mut player_anim, mut player_transform) = player_query.single_mut();
-    let speed = 170.0;
+    let (mut player_struct, mut velocity, mut gravity, mut player_anim, mut player_transform, mut ink_reserve, dizzy) = player_query.single_mut();
+    if player_struct.swimming {return} // see swimming.rs
+    let tuning = movement_config.preset();
+    let speed = if dizzy {tuning.run_speed * tuning.spin.dizzy_speed} else {tuning.run_speed};
+    // ink stained ground barely slows the squid down
+    let on_slick = feet_query.iter().any(|feet| feet.contacts.iter().any(|platform| slick_query.contains(*platform)));
+    let damping = (if on_slick {tuning.ink.slick_damping} else {tuning.damping}).powf(time.delta_secs());
+    let wall_jump_locked = player_struct.wall_jump_lock > 0.0;
+    player_struct.wall_jump_lock = (player_struct.wall_jump_lock - time.delta_secs()).max(0.0);
 
-    let mut movement_vector: Vec2 = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyA) {
-        movement_vector.x -= 1.0;
+    // analog sticks give partial speed, keys and the d-pad always give full speed
+    let movement_vector: Vec2 = Vec2::new(actions.move_axis().x, 0.0);
+    if movement_vector.x < 0.0 {
         player_anim.face = PlayerFace::Left;
-        
-    }
-    if input.pressed(KeyCode::KeyD) {
-        movement_vector.x += 1.0;
+    } else if movement_vector.x > 0.0 {
         player_anim.face = PlayerFace::Right;
     }
 
-    if movement_vector != Vec2::ZERO {
+    if wall_jump_locked {
+        // a wall jump keeps its push away from the wall for a moment, see wall.rs
+    } else if movement_vector != Vec2::ZERO {
         let movement = movement_vector.x * speed;
         if movement < 0. {
             if velocity.linvel.x > movement {
                 velocity.linvel.x = movement;
             } else {
-                velocity.linvel.x = velocity.linvel.x * 0.05_f32.powf(time.delta_secs()); // damping
+                velocity.linvel.x = velocity.linvel.x * damping;
             }
         }
         if movement > 0. {
             if velocity.linvel.x < movement {
                 velocity.linvel.x = movement;
             } else {
-                velocity.linvel.x = velocity.linvel.x * 0.05_f32.powf(time.delta_secs()); // damping
+                velocity.linvel.x = velocity.linvel.x * damping;
             }
         }
-        if dash_timer.timer.finished() {
+        if dash_timer.timer.finished() && !player_anim.is_busy() {
             player_anim.set_state(AnimState::Walk);
         }
     } else {
-        velocity.linvel.x = velocity.linvel.x * 0.05_f32.powf(time.delta_secs()); // damping
-        if dash_timer.timer.finished() {
+        velocity.linvel.x = velocity.linvel.x * damping;
+        if dash_timer.timer.finished() && !player_anim.is_busy() {
             player_anim.set_state(AnimState::Idle);
         }
     }
 
-    if input.just_pressed(KeyCode::Space) && player_struct.has_jump {
-        velocity.linvel.y = 300.0;
+    // on a slope run along the ground instead of into it or off it, flat ground is left to the physics
+    let normal = player_struct.ground_normal;
+    if player_struct.grounded && !wall_jump_locked && normal.y < 0.999 && normal.y > WALKABLE_NORMAL_Y {
+        let along_ground = Vec2::new(normal.y, -normal.x);
+        velocity.linvel = along_ground * velocity.linvel.x;
+    }
+
+    // coyote time keeps the jump for a moment after leaving a ledge,
+    // the jump buffer keeps a press from just before landing
+    if player_struct.grounded {
+        player_struct.coyote_time_left = tuning.coyote_time;
+    } else {
+        player_struct.coyote_time_left = (player_struct.coyote_time_left - time.delta_secs()).max(0.0);
+    }
+    // a press that dropped through a platform isn't buffered, it would jump off the platform below
+    let jump_pressed = actions.just_pressed(PlayerAction::Jump) && !player_struct.jump_used;
+    if jump_pressed {
+        player_struct.jump_buffer_left = tuning.jump_buffer;
+    } else {
+        player_struct.jump_buffer_left = (player_struct.jump_buffer_left - time.delta_secs()).max(0.0);
+    }
+
+    let can_jump = player_struct.has_jump || player_struct.coyote_time_left > 0.0;
+    if (jump_pressed || player_struct.jump_buffer_left > 0.0) && can_jump {
         player_struct.has_jump = false;
         player_struct.grounded = false;
-        spawn_splotch(&mut splotch_registry, 50, player_transform.translation.xy() + Vec2::new(0.0, -20.0));
+        player_struct.coyote_time_left = 0.0;
+        player_struct.jump_buffer_left = 0.0;
+        player_struct.jump_used = true;
+        // without ink the squid still jumps, just not as high
+        if ink_reserve.spend(tuning.ink.jump_cost) {
+            velocity.linvel.y = tuning.jump_speed;
+            spawn_splotch(&mut splotch_registry, 50, player_transform.translation.xy() + Vec2::new(0.0, -20.0));
+        } else {
+            velocity.linvel.y = tuning.jump_speed * tuning.ink.dry_jump;
+        }
     }
 
-    if input.pressed(KeyCode::Space) && velocity.linvel.y > 0.0 && (dash_timer.timer.finished() || dash_timer.direction != InputDirection::Up) {
-        gravity.0 = 0.7;
-    } else if input.pressed(KeyCode::KeyS) {
-        gravity.0 = 2.5;
+    if actions.pressed(PlayerAction::Jump) && velocity.linvel.y > 0.0 && (dash_timer.timer.finished() || dash_timer.direction != InputDirection::Up) {
+        gravity.0 = tuning.rise_gravity;
+    } else if actions.move_axis().y < 0.0 {
+        gravity.0 = tuning.dive_gravity;
     } else {
-        gravity.0 = 1.3;
+        gravity.0 = tuning.fall_gravity;
     } 
 
     if player_transform.translation.y < -1000.0 { //reset to origin for out of bounds
@@ -82,89 +132,176 @@ fn control_squid (
 
 }
 
+/// Dive and jump together drops through the passthrough platform the squid stands on
+pub(crate) fn drop_through_platforms (
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Player, Has<DropThrough>)>,
+    feet_query: Query<&FeetSensor>,
+    platform_query: Query<&Platform>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+) {
+    let Ok((player_entity, mut player_struct, dropping)) = player_query.get_single_mut() else {return};
+    // first of the movement systems, nothing has used this frame's jump press yet
+    player_struct.jump_used = false;
+    if dropping {return}
+    let Ok(feet) = feet_query.get_single() else {return};
+    if !(player_struct.grounded && actions.move_axis().y < 0.0 && actions.just_pressed(PlayerAction::Jump)) {return}
+    // can't drop while any part of the ground is solid
+    if !feet.contacts.iter().all(|platform| platform_query.get(*platform).is_ok_and(|platform| platform.is_passthrough())) {return}
+
+    commands.entity(player_entity).insert(DropThrough::new(movement_config.preset().drop_through_time));
+    // no jump this frame or from the buffer, control_squid sees a squid that already left the ground
+    player_struct.jump_used = true;
+    player_struct.grounded = false;
+    player_struct.has_jump = false;
+    player_struct.coyote_time_left = 0.0;
+    player_struct.jump_buffer_left = 0.0;
+}
+
+/// Track every platform under the feet sensor, the squid is grounded while any of them is touched
 fn manage_feet (
-    mut player_query: Query<(&mut Player, &Children)>,
-    mut platform_query: Query<Entity, With<Platform>>,
-    mut collision_events: EventReader<CollisionEvent>
+    mut player_query: Query<(Entity, &mut Player, &Velocity, &Transform, &Collider, Has<DropThrough>)>,
+    mut feet_query: Query<(Entity, &mut FeetSensor)>,
+    platform_query: Query<(&Platform, &GlobalTransform, &Collider)>,
+    mut collision_events: EventReader<CollisionEvent>,
+    rapier_context: ReadDefaultRapierContext,
 ) {
     if player_query.iter().count() == 0 {return}
-    if platform_query.iter().count() == 0 {return}
-    let (mut player_struct, player_children) = player_query.single_mut();
-    let feet_entity = player_children.iter().nth(1);
+    let Ok((feet_entity, mut feet)) = feet_query.get_single_mut() else {return};
+    let (player_entity, mut player_struct, velocity, player_transform, player_collider, dropping) = player_query.single_mut();
 
     for collision_event in collision_events.read() {
-        match collision_event {
-            CollisionEvent::Started(a, b, _) => {
-                if (feet_entity == Some(a) || feet_entity == Some(b)) && (platform_query.get_mut(*a).is_ok() || platform_query.get_mut(*b).is_ok()) {
-                    player_struct.grounded = true;
-                    player_struct.has_jump = true;
-                }
-            },
-            CollisionEvent::Stopped(a, b, _) => {
-                if (feet_entity == Some(a) || feet_entity == Some(b)) && (platform_query.get_mut(*a).is_ok() || platform_query.get_mut(*b).is_ok()) {
-                    player_struct.grounded = false;
-                    player_struct.has_jump = false;
-                }
+        let (a, b, started) = match collision_event {
+            CollisionEvent::Started(a, b, _) => (*a, *b, true),
+            CollisionEvent::Stopped(a, b, _) => (*a, *b, false),
+        };
+        let platform = if a == feet_entity {b} else if b == feet_entity {a} else {continue};
+        if !platform_query.contains(platform) {continue}
+        if started {
+            feet.contacts.insert(platform);
+        } else {
+            feet.contacts.remove(&platform);
+        }
+    }
+    // platforms despawned while touched don't always send a stop event
+    feet.contacts.retain(|platform| platform_query.contains(*platform));
+
+    // a jump leaves the sensor overlapping for a frame or two, only land while not moving up
+    let squid_bottom = player_transform.translation.y + player_collider.raw.compute_local_aabb().mins.y;
+    let touching = feet.contacts.iter().any(|platform| {
+        let Ok((platform, platform_transform, platform_collider)) = platform_query.get(*platform) else {return false};
+        if !platform.is_passthrough() {return true}
+        // passthrough platforms only hold the squid once it is on top, not while jumping up or falling through them
+        let platform_top = platform_transform.translation().y + platform_collider.raw.compute_local_aabb().maxs.y;
+        return !dropping && stands_on_top(squid_bottom, platform_top);
+    });
+    if touching && !player_struct.grounded && velocity.linvel.y <= 0.0 {
+        player_struct.grounded = true;
+        player_struct.has_jump = true;
+    } else if !touching {
+        player_struct.grounded = false;
+        player_struct.has_jump = false;
+    }
+
+    player_struct.ground_normal = Vec2::Y;
+    if touching {
+        let filter = QueryFilter::default().exclude_rigid_body(player_entity).exclude_sensors();
+        let hit = rapier_context.single().cast_ray_and_get_normal(player_transform.translation.xy(), Vec2::NEG_Y, 40.0, true, filter);
+        if let Some((entity, intersection)) = hit {
+            if feet.contacts.contains(&entity) {
+                player_struct.ground_normal = intersection.normal;
             }
         }
     }
 }
 
-fn manage_dash (
-    input_stack: ResMut<InputStack>,
-    mut player_query: Query<(&mut Velocity, &mut GravityScale, &mut PlayerAnimation, &Transform), With<Player>>,
+/// True once the squid's bottom is at or above a platform's top edge
+fn stands_on_top (squid_bottom: f32, platform_top: f32) -> bool {
+    return squid_bottom >= platform_top - PASSTHROUGH_LANDING_TOLERANCE;
+}
+
+pub(crate) fn manage_dash (
+    mut combo_events: EventReader<ComboPerformed>,
+    actions: Res<ActionState>,
+    mut player_query: Query<(&Player, &mut Velocity, &mut PlayerAnimation, &Transform, &mut InkReserve, Has<Dizzy>)>,
     mut dash_timer: ResMut<DashTimer>,
-    mut splotch_registry: ResMut<SplotchRegistry>
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    movement_config: Res<MovementConfig>,
 ) {
-    let last_two_inputs = input_stack.into_inner().stack.iter().rev().take(2).collect::<Vec<&(InputDirection, Instant)>>();
-    if last_two_inputs.len() != 2 {return}
-
-    let (direction_1, time_1) = last_two_inputs[0].clone();
-    let (direction_2, time_2) = last_two_inputs[1].clone();
-
-    if direction_1 == direction_2 {
-        match direction_1 {
-            InputDirection::Left => {
-                if time_1.elapsed().as_secs_f32() - time_2.elapsed().as_secs_f32() < 0.1 && time_2.elapsed().as_secs_f32() < dash_timer.timer.duration().as_secs_f32() && dash_timer.timer.finished() {
-                    player_query.single_mut().0.linvel.x = -500.0;
-                    dash_timer.timer.reset();
-                    dash_timer.direction = InputDirection::Left;
-                    spawn_splotch_cluster(&mut splotch_registry, 60, player_query.single_mut().3.translation.xy() + Vec2::new(10.0, -10.0));
-                }
-            }
-            InputDirection::Right => {
-                if time_1.elapsed().as_secs_f32() - time_2.elapsed().as_secs_f32() < 0.1 && time_2.elapsed().as_secs_f32() < dash_timer.timer.duration().as_secs_f32() && dash_timer.timer.finished() {
-                    player_query.single_mut().0.linvel.x = 500.0;
-                    dash_timer.timer.reset();
-                    dash_timer.direction = InputDirection::Right;
-                    spawn_splotch_cluster(&mut splotch_registry, 60, player_query.single_mut().3.translation.xy() + Vec2::new(-10.0, -10.0));
-                }
-            }
-            InputDirection::Up => {
-                if time_1.elapsed().as_secs_f32() - time_2.elapsed().as_secs_f32() < 0.1 && time_2.elapsed().as_secs_f32() < dash_timer.timer.duration().as_secs_f32() && dash_timer.timer.finished() {
-                    player_query.single_mut().0.linvel.y = 500.0;
-                    dash_timer.timer.reset();
-                    dash_timer.direction = InputDirection::Up;
-                    spawn_splotch_cluster(&mut splotch_registry, 60, player_query.single_mut().3.translation.xy() + Vec2::new(0.0, -20.0));
-                }
-            }
-            InputDirection::Down => {
-                if time_1.elapsed().as_secs_f32() - time_2.elapsed().as_secs_f32() < 0.1 && time_2.elapsed().as_secs_f32() < dash_timer.timer.duration().as_secs_f32() && dash_timer.timer.finished() {
-                    player_query.single_mut().0.linvel.y = -800.0;
-                    dash_timer.timer.reset();
-                    dash_timer.direction = InputDirection::Down;
-                    spawn_splotch_cluster(&mut splotch_registry, 60, player_query.single_mut().3.translation.xy() + Vec2::new(0.0, -20.0));
-                }
-            }
-            
+    if player_query.iter().count() == 0 {return}
+    let (player_struct, mut velocity, mut player_anim, transform, mut ink_reserve, dizzy) = player_query.single_mut();
+    // underwater dashes use the swim preset's speeds
+    let tuning = movement_config.current(player_struct.swimming);
+    // no dashing while dizzy, combos performed meanwhile are dropped
+    let can_dash = dash_timer.timer.finished() && !dizzy;
+
+    // double tap dashes are registered combos, see combos.rs
+    for combo in combo_events.read() {
+        let direction = match combo.name.as_str() {
+            DASH_LEFT => InputDirection::Left,
+            DASH_RIGHT => InputDirection::Right,
+            DASH_UP => InputDirection::Up,
+            DASH_DOWN => InputDirection::Down,
+            _ => continue,
+        };
+        if can_dash && dash_timer.timer.finished() && ink_reserve.spend(tuning.ink.dash_cost) {
+            start_dash(direction, &tuning, &mut velocity, &mut dash_timer, &mut splotch_registry, transform.translation.xy());
         }
     }
 
-    for (_, _, mut player_anim, _) in player_query.iter_mut() {
-        if !dash_timer.timer.finished() {
-            player_anim.set_state(AnimState::Dash);
-        }
+    // the dash button is an alternative to double tapping, it dashes the way the squid is pushed or facing
+    if can_dash && dash_timer.timer.finished() && actions.just_pressed(PlayerAction::Dash) && ink_reserve.spend(tuning.ink.dash_cost) {
+        let direction = dash_direction(actions.move_axis(), &player_anim.face);
+        start_dash(direction, &tuning, &mut velocity, &mut dash_timer, &mut splotch_registry, transform.translation.xy());
     }
+
+    if !dash_timer.timer.finished() && !player_anim.is_busy() {
+        player_anim.set_state(AnimState::Dash);
+    }
+}
+
+/// Which way the dash button dashes, vertical wins when the stick leans up or down at least as far as sideways
+fn dash_direction (axis: Vec2, face: &PlayerFace) -> InputDirection {
+    if axis.y != 0.0 && axis.y.abs() >= axis.x.abs() {
+        return if axis.y < 0.0 {InputDirection::Down} else {InputDirection::Up};
+    }
+    if axis.x < 0.0 || (axis.x == 0.0 && *face == PlayerFace::Left) {
+        return InputDirection::Left;
+    }
+    return InputDirection::Right;
+}
+
+fn start_dash (
+    direction: InputDirection,
+    tuning: &MovementPreset,
+    velocity: &mut Velocity,
+    dash_timer: &mut DashTimer,
+    splotch_registry: &mut ResMut<SplotchRegistry>,
+    position: Vec2,
+) {
+    let splotch_offset = match direction {
+        InputDirection::Left => {
+            velocity.linvel.x = -tuning.dash_speed;
+            Vec2::new(10.0, -10.0)
+        }
+        InputDirection::Right => {
+            velocity.linvel.x = tuning.dash_speed;
+            Vec2::new(-10.0, -10.0)
+        }
+        InputDirection::Up => {
+            velocity.linvel.y = tuning.dash_up_speed;
+            Vec2::new(0.0, -20.0)
+        }
+        InputDirection::Down => {
+            velocity.linvel.y = -tuning.dash_down_speed;
+            Vec2::new(0.0, -20.0)
+        }
+    };
+    dash_timer.timer.set_duration(Duration::from_secs_f32(tuning.dash_duration));
+    dash_timer.timer.reset();
+    dash_timer.direction = direction;
+    spawn_splotch_cluster(splotch_registry, 60, position + splotch_offset);
 }
 
 #[derive(Resource)]
@@ -182,7 +319,7 @@ impl DashTimer {
         }
     }
 }
-fn tick_dash_timer (
+pub(crate) fn tick_dash_timer (
     mut dash_timer: ResMut<DashTimer>,
     time: Res<Time>,
     mut splotch_registry: ResMut<SplotchRegistry>,
@@ -199,4 +336,208 @@ fn tick_dash_timer (
             );
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use crate::player_character::test_app::*;
+
+    #[test]
+    fn dash_direction_follows_the_stick () {
+        assert_eq!(dash_direction(Vec2::new(0.2, -0.8), &PlayerFace::Right), InputDirection::Down);
+        assert_eq!(dash_direction(Vec2::new(-0.3, 0.6), &PlayerFace::Right), InputDirection::Up);
+        assert_eq!(dash_direction(Vec2::new(-0.8, 0.3), &PlayerFace::Right), InputDirection::Left);
+        // the keyboard's diagonals dash vertically
+        assert_eq!(dash_direction(Vec2::new(1.0, -1.0), &PlayerFace::Right), InputDirection::Down);
+    }
+
+    #[test]
+    fn dash_direction_falls_back_to_facing () {
+        assert_eq!(dash_direction(Vec2::ZERO, &PlayerFace::Left), InputDirection::Left);
+        assert_eq!(dash_direction(Vec2::ZERO, &PlayerFace::Right), InputDirection::Right);
+    }
+
+    /// A squid in the air without a jump of its own, only the drop and control systems run, ground is set by hand
+    fn ledge_app () -> (App, Entity) {
+        let mut app = squid_app();
+        set_frame_time(&mut app, 0.02);
+        app.insert_resource(DashTimer::new(MovementPreset::default().dash_duration));
+        app.add_systems(Update, (drop_through_platforms, control_squid).chain());
+        let squid = spawn_squid(&mut app);
+        app.world_mut().entity_mut(squid).insert((GravityScale(1.0), PlayerAnimation::default()));
+        return (app, squid);
+    }
+
+    /// Stands in for manage_feet
+    fn set_grounded (app: &mut App, squid: Entity, grounded: bool) {
+        let mut player = app.world_mut().get_mut::<Player>(squid).unwrap();
+        player.grounded = grounded;
+        player.has_jump = grounded;
+    }
+
+    fn rising_speed (app: &App, squid: Entity) -> f32 {
+        return app.world().get::<Velocity>(squid).unwrap().linvel.y;
+    }
+
+    #[test]
+    fn jump_just_after_leaving_a_ledge_uses_coyote_time () {
+        let (mut app, squid) = ledge_app();
+        set_grounded(&mut app, squid, true);
+        app.update();
+        set_grounded(&mut app, squid, false);
+        // 40 ms off the ledge, well inside the 100 ms window
+        app.update();
+        app.update();
+        hold(&mut app, PlayerAction::Jump, true);
+        app.update();
+        assert_eq!(rising_speed(&app, squid), MovementPreset::default().jump_speed);
+    }
+
+    #[test]
+    fn jump_after_the_coyote_window_does_nothing () {
+        let (mut app, squid) = ledge_app();
+        set_grounded(&mut app, squid, true);
+        app.update();
+        set_grounded(&mut app, squid, false);
+        for _ in 0..7 {
+            app.update();
+        }
+        hold(&mut app, PlayerAction::Jump, true);
+        app.update();
+        assert_eq!(rising_speed(&app, squid), 0.0);
+        assert!(!app.world().get::<Player>(squid).unwrap().has_jump);
+    }
+
+    #[test]
+    fn jump_pressed_just_before_landing_fires_on_landing () {
+        let (mut app, squid) = ledge_app();
+        app.update();
+        hold(&mut app, PlayerAction::Jump, true);
+        app.update();
+        assert_eq!(rising_speed(&app, squid), 0.0);
+        hold(&mut app, PlayerAction::Jump, false);
+        app.update();
+        set_grounded(&mut app, squid, true);
+        app.update();
+        assert_eq!(rising_speed(&app, squid), MovementPreset::default().jump_speed);
+    }
+
+    /// Bottom of the passthrough platform in [`jump_through_app`] is at 118, its top at 182
+    const PLATFORM_TOP: f32 = 182.0;
+    /// Squid center to the bottom of its capsule
+    const SQUID_HALF_HEIGHT: f32 = 23.0;
+
+    /// A squid at the origin with its feet sensor, only manage_feet runs besides the physics
+    fn feet_app (velocity: Vec2) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin, AssetPlugin::default()));
+        app.init_asset::<Mesh>();
+        app.add_plugins((RapierPhysicsPlugin::<OneWayPlatformHooks>::pixels_per_meter(64.0), PlatformPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(1.0 / 60.0)));
+        app.add_systems(Update, manage_feet);
+        let squid = app.world_mut().spawn((
+            Transform::default(),
+            RigidBody::Dynamic,
+            Collider::capsule_y(3., 20.),
+            ActiveEvents::COLLISION_EVENTS,
+            Velocity::linear(velocity),
+            LockedAxes::ROTATION_LOCKED,
+            Player::new(),
+        )).with_children(|parent| {
+            parent.spawn((
+                FeetSensor::default(),
+                Transform::from_translation(Vec3::new(0.0, -10.0, 0.0)),
+                Collider::ball(16.),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+            ));
+        }).id();
+        return (app, squid);
+    }
+
+    /// A squid jumping up into a passthrough platform
+    fn jump_through_app (jump_speed: f32) -> (App, Entity) {
+        let (mut app, squid) = feet_app(Vec2::new(0.0, jump_speed));
+        app.world_mut().spawn((
+            Transform::from_xyz(0.0, 150.0, 0.0),
+            RigidBody::Fixed,
+            Collider::cuboid(128., 32.),
+            Platform::PASSTHROUGH,
+        ));
+        return (app, squid);
+    }
+
+    /// Steps the app, checking the squid is never grounded while its bottom is inside the platform
+    ///
+    /// Returns whether the feet sensor touched the platform from inside it
+    fn run_jump (app: &mut App, squid: Entity, frames: usize) -> bool {
+        let mut touched_inside = false;
+        for _ in 0..frames {
+            app.update();
+            let squid_bottom = app.world().get::<Transform>(squid).unwrap().translation.y - SQUID_HALF_HEIGHT;
+            let feet_touching = !app.world_mut().query::<&FeetSensor>().single(app.world()).contacts.is_empty();
+            let player = app.world().get::<Player>(squid).unwrap();
+            if squid_bottom < PLATFORM_TOP - PASSTHROUGH_LANDING_TOLERANCE {
+                touched_inside |= feet_touching;
+                assert!(!player.grounded && !player.has_jump, "grounded inside the platform at {}", squid_bottom);
+            }
+        }
+        return touched_inside;
+    }
+
+    #[test]
+    fn jump_peaking_inside_a_passthrough_platform_gives_no_jump () {
+        // peaks with the squid's bottom about 25px under the platform's top
+        let (mut app, squid) = jump_through_app(475.0);
+        assert!(run_jump(&mut app, squid, 90), "the feet should have touched the platform");
+        // it falls back out the bottom
+        let squid_bottom = app.world().get::<Transform>(squid).unwrap().translation.y - SQUID_HALF_HEIGHT;
+        assert!(squid_bottom < PLATFORM_TOP - 64.0);
+        assert!(!app.world().get::<Player>(squid).unwrap().grounded);
+    }
+
+    #[test]
+    fn jumping_up_through_a_passthrough_platform_lands_on_top () {
+        let (mut app, squid) = jump_through_app(600.0);
+        assert!(run_jump(&mut app, squid, 180), "the feet should have touched the platform on the way up");
+        let player = app.world().get::<Player>(squid).unwrap();
+        assert!(player.grounded && player.has_jump);
+        let squid_bottom = app.world().get::<Transform>(squid).unwrap().translation.y - SQUID_HALF_HEIGHT;
+        assert!((squid_bottom - PLATFORM_TOP).abs() < PASSTHROUGH_LANDING_TOLERANCE);
+    }
+
+    #[test]
+    fn leaving_one_of_two_overlapping_platforms_keeps_the_squid_grounded () {
+        let (mut app, squid) = feet_app(Vec2::ZERO);
+        let left = app.world_mut().spawn((Transform::from_xyz(-100.0, -56.0, 0.0), RigidBody::Fixed, Collider::cuboid(128., 32.), Platform::SOLID)).id();
+        let right = app.world_mut().spawn((Transform::from_xyz(100.0, -56.0, 0.0), RigidBody::Fixed, Collider::cuboid(128., 32.), Platform::SOLID)).id();
+        for _ in 0..60 {
+            app.update();
+        }
+        let contacts = app.world_mut().query::<&FeetSensor>().single(app.world()).contacts.clone();
+        assert!(contacts.contains(&left) && contacts.contains(&right));
+        assert!(app.world().get::<Player>(squid).unwrap().grounded);
+
+        // the left platform slides away, the squid still stands on the right one
+        app.world_mut().get_mut::<Transform>(left).unwrap().translation.x = -1000.0;
+        for _ in 0..10 {
+            app.update();
+            assert!(app.world().get::<Player>(squid).unwrap().grounded);
+        }
+        let contacts = app.world_mut().query::<&FeetSensor>().single(app.world()).contacts.clone();
+        assert!(!contacts.contains(&left) && contacts.contains(&right));
+
+        app.world_mut().get_mut::<Transform>(right).unwrap().translation.x = 1000.0;
+        app.update();
+        app.update();
+        assert!(!app.world().get::<Player>(squid).unwrap().grounded);
+    }
+
+    #[test]
+    fn passthrough_ground_starts_at_the_top_edge () {
+        assert!(stands_on_top(182.0, 182.0));
+        assert!(stands_on_top(180.0, 182.0));
+        assert!(!stands_on_top(160.0, 182.0));
+    }
+}
@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::collections::HashSet;
+use crate::player_character::*;
+use rand::*;
+
+/// Hold charge to build up a launch, release to fly the way the squid is pushed or facing
+///
+/// Launch speed and damage scale with how long the button was held. While launching the squid
+/// carries a hitbox that damages anything with [`Health`] except itself.
+pub struct ChargePlugin;
+
+impl Plugin for ChargePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChargeState>();
+        app.add_systems(Update, (
+            spawn_charge_indicator,
+            // after control_squid, its slope projection would bend a launch along the ground
+            (manage_charge, update_charge_indicator).chain().after(MovementSystems),
+            end_launch,
+            apply_charge_hits,
+        ));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ChargeState {
+    pub charging: bool,
+    /// Seconds held, up to the full charge time
+    pub held: f32,
+}
+
+impl ChargeState {
+    /// Between 0 and 1
+    pub fn fraction (&self, tuning: &ChargeTuning) -> f32 {
+        if tuning.full_charge_time <= 0.0 {return 1.0}
+        return (self.held / tuning.full_charge_time).min(1.0);
+    }
+}
+
+/// Bar above the squid that fills while charging
+#[derive(Component)]
+struct ChargeIndicator;
+
+const INDICATOR_WIDTH: f32 = 40.0;
+
+/// Sensor carried during a launch or a spin, each entity is only hit once per attack
+#[derive(Component)]
+pub struct ChargeHitbox {
+    pub damage: f32,
+    pub timer: Timer,
+    pub hit: HashSet<Entity>,
+}
+
+fn spawn_charge_indicator (
+    mut commands: Commands,
+    player_query: Query<Entity, Added<Player>>,
+) {
+    for player_entity in player_query.iter() {
+        commands.entity(player_entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("charge indicator"),
+                Sprite {
+                    color: Color::srgb(0.6, 0.3, 0.9),
+                    custom_size: Some(Vec2::new(INDICATOR_WIDTH, 4.0)),
+                    ..default()
+                },
+                Transform::from_translation(Vec3::new(0.0, 36.0, 1.0)),
+                Visibility::Hidden,
+                ChargeIndicator,
+            ));
+        });
+    }
+}
+
+fn manage_charge (
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &Player, &mut Velocity, &PlayerAnimation, &Transform, &mut InkReserve, Has<Dizzy>)>,
+    hitbox_query: Query<(), With<ChargeHitbox>>,
+    mut charge: ResMut<ChargeState>,
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+    time: Res<Time>,
+) {
+    let Ok((player_entity, player_struct, mut velocity, player_anim, transform, mut ink_reserve, dizzy)) = player_query.get_single_mut() else {return};
+    // no charging while dizzy, a charge in progress fizzles out
+    if dizzy {
+        charge.charging = false;
+        charge.held = 0.0;
+        return;
+    }
+    let preset = movement_config.current(player_struct.swimming);
+    let tuning = &preset.charge;
+
+    // the ink is paid up front, no ink no charge
+    if actions.just_pressed(PlayerAction::Charge) && hitbox_query.is_empty() && ink_reserve.spend(preset.ink.charge_cost) {
+        charge.charging = true;
+        charge.held = 0.0;
+    }
+    if !charge.charging {return}
+
+    if actions.pressed(PlayerAction::Charge) {
+        charge.held = (charge.held + time.delta_secs()).min(tuning.full_charge_time);
+        // ink drips off the squid faster the more it is charged, staining the ground under it
+        let mut rng = rand::thread_rng();
+        if rng.gen_range(0.0..1.0) < 0.1 + charge.fraction(tuning) * 0.3 {
+            let offset = Vec2::new(rng.gen_range(-16.0..16.0), -20.0);
+            spawn_ink_stain(&mut splotch_registry, 20 + (charge.fraction(tuning) * 25.0) as usize, transform.translation.xy() + offset);
+        }
+        return;
+    }
+
+    // released, launch
+    let fraction = charge.fraction(tuning);
+    charge.charging = false;
+    charge.held = 0.0;
+
+    let axis = actions.move_axis();
+    let direction = if axis != Vec2::ZERO {
+        axis.normalize()
+    } else if player_anim.face == PlayerFace::Left {
+        Vec2::NEG_X
+    } else {
+        Vec2::X
+    };
+    velocity.linvel = direction * (tuning.min_speed + (tuning.max_speed - tuning.min_speed) * fraction);
+    spawn_splotch_cluster(&mut splotch_registry, 40 + (fraction * 30.0) as usize, transform.translation.xy() - direction * 20.0);
+
+    commands.entity(player_entity).with_children(|parent| {
+        parent.spawn((
+            Name::new("charge hitbox"),
+            Collider::ball(tuning.hitbox_radius),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            ChargeHitbox {
+                damage: tuning.min_damage + (tuning.max_damage - tuning.min_damage) * fraction,
+                timer: Timer::from_seconds(tuning.launch_time, TimerMode::Once),
+                hit: HashSet::new(),
+            },
+        ));
+    });
+}
+
+fn update_charge_indicator (
+    mut indicator_query: Query<(&mut Sprite, &mut Visibility), With<ChargeIndicator>>,
+    player_query: Query<&Player>,
+    charge: Res<ChargeState>,
+    movement_config: Res<MovementConfig>,
+) {
+    let Ok(player_struct) = player_query.get_single() else {return};
+    let tuning = &movement_config.current(player_struct.swimming).charge;
+    for (mut sprite, mut visibility) in indicator_query.iter_mut() {
+        if !charge.charging {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+        let fraction = charge.fraction(tuning);
+        sprite.custom_size = Some(Vec2::new(INDICATOR_WIDTH * fraction, 4.0));
+        // flashes white once fully charged
+        sprite.color = if fraction >= 1.0 {Color::WHITE} else {Color::srgb(0.6, 0.3, 0.9)};
+    }
+}
+
+fn end_launch (
+    mut commands: Commands,
+    mut hitbox_query: Query<(Entity, &mut ChargeHitbox)>,
+    time: Res<Time>,
+) {
+    for (entity, mut hitbox) in hitbox_query.iter_mut() {
+        hitbox.timer.tick(time.delta());
+        if hitbox.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Damage anything with health the hitbox touches, the squid itself is never hurt
+fn apply_charge_hits (
+    mut collision_events: EventReader<CollisionEvent>,
+    mut hitbox_query: Query<&mut ChargeHitbox>,
+    mut target_query: Query<&mut Health, Without<Player>>,
+) {
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(a, b, _) = collision_event else {continue};
+        let (hitbox_entity, target) = if hitbox_query.contains(*a) {(*a, *b)} else if hitbox_query.contains(*b) {(*b, *a)} else {continue};
+        let Ok(mut hitbox) = hitbox_query.get_mut(hitbox_entity) else {continue};
+        let Ok(mut health) = target_query.get_mut(target) else {continue};
+        if !hitbox.hit.insert(target) {continue}
+        health.damage(hitbox.damage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_character::test_app::*;
+
+    fn charge_app () -> (App, Entity) {
+        let mut app = squid_app();
+        set_frame_time(&mut app, 0.1);
+        app.init_resource::<ChargeState>();
+        app.add_event::<CollisionEvent>();
+        app.add_systems(Update, (manage_charge, apply_charge_hits));
+        let squid = spawn_squid(&mut app);
+        return (app, squid);
+    }
+
+    #[test]
+    fn full_charge_launches_at_max_speed_with_a_hitbox () {
+        let (mut app, squid) = charge_app();
+        let tuning = ChargeTuning::default();
+        set_move_axis(&mut app, Vec2::NEG_X);
+        // held well past the full charge time
+        for _ in 0..20 {
+            hold(&mut app, PlayerAction::Charge, true);
+            app.update();
+        }
+        assert!(app.world().resource::<ChargeState>().charging);
+        assert_eq!(app.world().get::<InkReserve>(squid).unwrap().ink, 100.0 - InkTuning::default().charge_cost);
+        hold(&mut app, PlayerAction::Charge, false);
+        app.update();
+
+        assert!(!app.world().resource::<ChargeState>().charging);
+        assert_eq!(app.world().get::<Velocity>(squid).unwrap().linvel, Vec2::new(-tuning.max_speed, 0.0));
+        let children = app.world().get::<Children>(squid).expect("the launch should add a hitbox");
+        let hitbox = app.world().get::<ChargeHitbox>(children[0]).unwrap();
+        assert_eq!(hitbox.damage, tuning.max_damage);
+    }
+
+    #[test]
+    fn hitbox_damages_each_target_once () {
+        let (mut app, _) = charge_app();
+        let hitbox = app.world_mut().spawn(ChargeHitbox {
+            damage: 12.0,
+            timer: Timer::from_seconds(1.0, TimerMode::Once),
+            hit: HashSet::new(),
+        }).id();
+        let rat = app.world_mut().spawn(Health::new(30.0)).id();
+        for _ in 0..2 {
+            app.world_mut().send_event(CollisionEvent::Started(hitbox, rat, bevy_rapier2d::rapier::geometry::CollisionEventFlags::SENSOR));
+            app.update();
+        }
+        assert_eq!(app.world().get::<Health>(rat).unwrap().health, 18.0);
+    }
+
+    #[test]
+    fn no_charge_without_ink () {
+        let (mut app, squid) = charge_app();
+        app.world_mut().get_mut::<InkReserve>(squid).unwrap().ink = 10.0;
+        hold(&mut app, PlayerAction::Charge, true);
+        app.update();
+        assert!(!app.world().resource::<ChargeState>().charging);
+        assert_eq!(app.world().get::<InkReserve>(squid).unwrap().ink, 10.0);
+    }
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use crate::flex_load::*;
+use super::input_track::*;
+use super::movement_config::*;
+use super::player::Player;
+
+pub struct CombosPlugin;
+
+impl Plugin for CombosPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ComboRegistry::with_dashes(MovementPreset::default().dash_duration));
+        app.add_event::<ComboPerformed>();
+        app.add_systems(Update, (
+            (sync_dash_window, fit_input_stack_to_combos).chain().before(track_input),
+            detect_combos.after(track_input),
+        ).run_if(in_state(AssetLoadState::Ready)));
+    }
+}
+
+pub const DASH_LEFT: &str = "dash_left";
+pub const DASH_RIGHT: &str = "dash_right";
+pub const DASH_UP: &str = "dash_up";
+pub const DASH_DOWN: &str = "dash_down";
+const DASHES: [&str; 4] = [DASH_LEFT, DASH_RIGHT, DASH_UP, DASH_DOWN];
+
+/// A named sequence of directions that must all be pressed within `window` seconds
+#[derive(Clone, Debug)]
+pub struct Combo {
+    pub name: String,
+    pub sequence: Vec<InputDirection>,
+    pub window: f32,
+}
+
+/// Sent once every time the last input of a registered combo is pressed
+#[derive(Event, Clone, Debug)]
+pub struct ComboPerformed {
+    pub name: String,
+}
+
+#[derive(Resource, Default)]
+pub struct ComboRegistry {
+    pub combos: Vec<Combo>,
+    last_seen_push: u64,
+}
+
+impl ComboRegistry {
+    /// The double tap dashes in every direction, a double tap must fit in `window` seconds
+    pub fn with_dashes(window: f32) -> Self {
+        let mut registry = Self::default();
+        registry.register(DASH_LEFT, vec![InputDirection::Left, InputDirection::Left], window);
+        registry.register(DASH_RIGHT, vec![InputDirection::Right, InputDirection::Right], window);
+        registry.register(DASH_UP, vec![InputDirection::Up, InputDirection::Up], window);
+        registry.register(DASH_DOWN, vec![InputDirection::Down, InputDirection::Down], window);
+        return registry;
+    }
+    /// Add a combo, replacing any existing combo with the same name
+    pub fn register(&mut self, name: &str, sequence: Vec<InputDirection>, window: f32) {
+        self.unregister(name);
+        self.combos.push(Combo {
+            name: name.to_string(),
+            sequence,
+            window,
+        });
+    }
+    pub fn unregister(&mut self, name: &str) {
+        self.combos.retain(|combo| combo.name != name);
+    }
+    pub fn get(&self, name: &str) -> Option<&Combo> {
+        return self.combos.iter().find(|combo| combo.name == name);
+    }
+    /// Seconds the input stack has to remember for every combo to be able to match
+    pub fn longest_window(&self) -> f32 {
+        return self.combos.iter().map(|combo| combo.window).fold(0.0, f32::max);
+    }
+    pub fn longest_sequence(&self) -> usize {
+        return self.combos.iter().map(|combo| combo.sequence.len()).max().unwrap_or(0);
+    }
+}
+
+impl Combo {
+    pub fn matches(&self, input_stack: &InputStack) -> bool {
+        let Some(newest_first) = input_stack.last_within(self.sequence.len(), self.window) else {return false};
+        return newest_first.iter().rev().eq(self.sequence.iter());
+    }
+}
+
+/// The double tap window is the dash duration of the preset in use, kept up to date on reloads and in water
+fn sync_dash_window (
+    mut registry: ResMut<ComboRegistry>,
+    movement_config: Res<MovementConfig>,
+    player_query: Query<&Player>,
+) {
+    let swimming = player_query.get_single().is_ok_and(|player| player.swimming);
+    let window = movement_config.current(swimming).dash_duration;
+    let is_dash = |combo: &Combo| DASHES.contains(&combo.name.as_str());
+    if registry.combos.iter().all(|combo| !is_dash(combo) || combo.window == window) {return}
+    for combo in registry.combos.iter_mut().filter(|combo| is_dash(combo)) {
+        combo.window = window;
+    }
+}
+
+/// Combos registered with a long window or sequence would otherwise lose their first inputs to eviction
+fn fit_input_stack_to_combos (
+    registry: Res<ComboRegistry>,
+    mut input_stack: ResMut<InputStack>,
+) {
+    if !registry.is_changed() {return}
+    input_stack.keep_at_least(registry.longest_window(), registry.longest_sequence());
+}
+
+pub fn detect_combos (
+    input_stack: Res<InputStack>,
+    mut registry: ResMut<ComboRegistry>,
+    mut combo_events: EventWriter<ComboPerformed>,
+) {
+    // only check when something new was pressed, so a combo fires once per completion
+    if input_stack.total_pushed() == registry.last_seen_push {return}
+    registry.last_seen_push = input_stack.total_pushed();
+
+    for combo in registry.combos.iter() {
+        if combo.matches(&input_stack) {
+            combo_events.send(ComboPerformed { name: combo.name.clone() });
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn down_down_up (window: f32) -> Combo {
+        return Combo {
+            name: "down_down_up".to_string(),
+            sequence: vec![InputDirection::Down, InputDirection::Down, InputDirection::Up],
+            window,
+        };
+    }
+
+    #[test]
+    fn combo_matches_its_sequence_in_order () {
+        let combo = down_down_up(0.5);
+        assert!(combo.matches(&InputStack::from_entries(&[(InputDirection::Down, 0.0), (InputDirection::Down, 0.125), (InputDirection::Up, 0.25)], 0.25)));
+        assert!(!combo.matches(&InputStack::from_entries(&[(InputDirection::Down, 0.0), (InputDirection::Up, 0.125), (InputDirection::Down, 0.25)], 0.25)));
+        // only the newest inputs count
+        assert!(!combo.matches(&InputStack::from_entries(&[(InputDirection::Down, 0.0), (InputDirection::Down, 0.125), (InputDirection::Up, 0.25), (InputDirection::Left, 0.375)], 0.375)));
+    }
+
+    #[test]
+    fn combo_must_fit_in_its_window () {
+        let inputs = [(InputDirection::Down, 0.0), (InputDirection::Down, 0.125), (InputDirection::Up, 0.25)];
+        assert!(down_down_up(0.25).matches(&InputStack::from_entries(&inputs, 0.25)));
+        assert!(!down_down_up(0.125).matches(&InputStack::from_entries(&inputs, 0.25)));
+    }
+
+    #[test]
+    fn register_replaces_a_combo_with_the_same_name () {
+        let mut registry = ComboRegistry::with_dashes(0.5);
+        registry.register(DASH_LEFT, vec![InputDirection::Left, InputDirection::Left, InputDirection::Left], 0.75);
+        assert_eq!(registry.combos.len(), 4);
+        assert_eq!(registry.get(DASH_LEFT).map(|combo| combo.sequence.len()), Some(3));
+        registry.unregister(DASH_LEFT);
+        assert!(registry.get(DASH_LEFT).is_none());
+    }
+
+    #[test]
+    fn input_stack_remembers_long_combos () {
+        let mut registry = ComboRegistry::with_dashes(0.5);
+        registry.register("slow", vec![InputDirection::Left, InputDirection::Right], 3.0);
+        let mut stack = InputStack::new();
+        stack.keep_at_least(registry.longest_window(), registry.longest_sequence());
+        stack.tick(0.0);
+        stack.push(InputDirection::Left);
+        stack.tick(2.5);
+        stack.push(InputDirection::Right);
+        assert!(registry.get("slow").unwrap().matches(&stack));
+    }
+}
@@ -1,22 +1,24 @@
 use bevy::prelude::*;
 use bevy::render::render_asset::RenderAssetUsages;
 use bevy::render::render_resource::{TextureDimension, TextureFormat, Extent3d};
+use bevy_rapier2d::prelude::*;
 use noise::{NoiseFn, Perlin};
 use rand::Rng;
+use std::collections::HashMap;
+use crate::scenes::*;
 
 pub struct InkPlugin;
 
 impl Plugin for InkPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SplotchRegistry {
-            to_spawn: Vec::new(),
-            spawned: Vec::new(),
-        });
-        app.add_systems(Update, (manage_spawns, tick_splotch).chain());
+        app.init_resource::<SplotchRegistry>();
+        app.init_resource::<SplotchPool>();
+        app.add_systems(Startup, generate_splotch_textures);
+        app.add_systems(Update, (manage_spawns, stain_platforms, tick_splotch, restore_unstained_platforms).chain());
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct SplotchRegistry {
     pub to_spawn: Vec<Splotch>,
     pub spawned: Vec<(Splotch, Entity)>,
@@ -28,6 +30,54 @@ pub struct Splotch {
     pub timer: Timer,
     pub position: Vec2,
     pub image: Handle<Image>,
+    pub effect: SplotchEffect,
+}
+
+/// What a splotch does besides being drawn
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum SplotchEffect {
+    /// Decoration only
+    #[default] None,
+    /// Deliberately spilled ink, stains the platform it lands on, see [`SlickPlatform`]
+    Stain,
+    /// Lasting cloud enemies can't see through, see [`InkCloud`], stains any platform it covers
+    Cloud { lifetime: f32 },
+}
+
+/// Ink cloud splotch, blocks enemy sight until its timer runs out and it starts fading
+#[derive(Component)]
+pub struct InkCloud {
+    pub radius: f32,
+    pub timer: Timer,
+}
+
+impl InkCloud {
+    /// True if the cloud covers any part of the line between two points, once it fades it covers nothing
+    pub fn blocks (&self, center: Vec2, from: Vec2, to: Vec2) -> bool {
+        if self.timer.finished() {return false}
+        let line = to - from;
+        let along = if line.length_squared() == 0.0 {0.0} else {((center - from).dot(line) / line.length_squared()).clamp(0.0, 1.0)};
+        return (from + line * along).distance(center) <= self.radius;
+    }
+}
+
+/// True if any ink cloud is between the two points
+pub fn hidden_by_ink (clouds: &Query<(&InkCloud, &Transform)>, from: Vec2, to: Vec2) -> bool {
+    return clouds.iter().any(|(cloud, transform)| cloud.blocks(transform.translation.xy(), from, to));
+}
+
+/// Splotch that landed on a platform, the platform is slick while any stain is on it
+#[derive(Component)]
+pub struct InkStain {
+    pub platform: Entity,
+}
+
+/// Stained platform, its friction is put back once the last stain on it fades
+#[derive(Component)]
+pub struct SlickPlatform {
+    pub original: Friction,
+    /// Stains on the platform, counted up as they land and down as they fade
+    pub stains: usize,
 }
 
 pub fn spawn_splotch (
@@ -40,6 +90,36 @@ pub fn spawn_splotch (
         timer: Timer::from_seconds(0.25, TimerMode::Repeating),
         position,
         image: Handle::default(),
+        effect: SplotchEffect::None,
+    });
+}
+
+pub fn spawn_ink_stain (
+    splotch_registry: &mut ResMut<SplotchRegistry>,
+    size: usize,
+    position: Vec2,
+) {
+    splotch_registry.to_spawn.push(Splotch {
+        size,
+        timer: Timer::from_seconds(0.25, TimerMode::Repeating),
+        position,
+        image: Handle::default(),
+        effect: SplotchEffect::Stain,
+    });
+}
+
+pub fn spawn_ink_cloud (
+    splotch_registry: &mut ResMut<SplotchRegistry>,
+    size: usize,
+    position: Vec2,
+    lifetime: f32,
+) {
+    splotch_registry.to_spawn.push(Splotch {
+        size,
+        timer: Timer::from_seconds(0.25, TimerMode::Repeating),
+        position,
+        image: Handle::default(),
+        effect: SplotchEffect::Cloud { lifetime },
     });
 }
 
@@ -71,80 +151,159 @@ pub fn spawn_splotch_cluster (
     }
 }
 
-pub fn manage_spawns (
+/// Splotch sizes share textures, a size uses the smallest bucket it fits in
+pub const SPLOTCH_SIZE_BUCKETS: [usize; 6] = [16, 32, 48, 64, 96, 128];
+/// Textures generated per size bucket
+pub const SPLOTCH_VARIANTS: usize = 4;
+/// Seed for the first variant, the textures come out the same every run
+const SPLOTCH_SEED: u32 = 1000;
+
+/// Splotch textures generated once at startup and shared by every splotch
+#[derive(Resource)]
+pub struct SplotchTextures {
+    buckets: Vec<(usize, Vec<Handle<Image>>)>,
+}
+
+impl SplotchTextures {
+    pub fn generate (images: &mut Assets<Image>) -> Self {
+        let buckets = SPLOTCH_SIZE_BUCKETS.iter().enumerate().map(|(bucket_index, bucket)| {
+            let variants = (0..SPLOTCH_VARIANTS).map(|variant| {
+                let seed = SPLOTCH_SEED + (bucket_index * SPLOTCH_VARIANTS + variant) as u32;
+                return images.add(splotch_image(bucket / 2, seed));
+            }).collect();
+            return (*bucket, variants);
+        }).collect();
+        return Self { buckets };
+    }
+    /// Texture for a splotch of `size`, `variant` wraps around
+    pub fn get (&self, size: usize, variant: usize) -> Handle<Image> {
+        let (_, variants) = self.buckets.iter()
+            .find(|(bucket, _)| size <= *bucket)
+            .unwrap_or(&self.buckets[self.buckets.len() - 1]);
+        return variants[variant % variants.len()].clone();
+    }
+}
+
+/// Faded splotch entities kept hidden for reuse instead of being despawned
+#[derive(Resource, Default)]
+pub struct SplotchPool {
+    pub free: Vec<Entity>,
+}
+
+fn generate_splotch_textures (
     mut commands: Commands,
-    mut splotch_registry: ResMut<SplotchRegistry>,
     mut images: ResMut<Assets<Image>>,
 ) {
-    for splotch in splotch_registry.to_spawn.iter() {
-        let mut rng = rand::thread_rng();
-        let perlin = Perlin::new(rng.gen_range(0..10000));
-        let size = splotch.size;
-        let scaled_size = size / 2;
-
-        let mut texture_data = vec![0u8; scaled_size * scaled_size * 4];
-        for y in 0..scaled_size {
-            for x in 0..scaled_size {
-                let value = perlin.get([5.0 * x as f64 / scaled_size as f64, 10.0 * y as f64 / scaled_size as f64]);
-                let x_weight = 1.0 - (2.0 * (x as f64 / scaled_size as f64 - 0.5)).abs(); // linear 0.0 to 1.0 (0.0 at the edges, 1.0 in the middle)
-                let y_weight = 1.0 - (2.0 * (y as f64 / scaled_size as f64 - 0.5)).abs();
-                let weight = x_weight * y_weight;
-                let value = (value + (weight * 1.5)) * weight;
-                let mut pixel_value = ((value * 1000.0).min(255.0).max(0.0)) as u8;
-
-                if pixel_value > 150 { // make toon
-                    pixel_value = 200;
-                } else {
-                    pixel_value = 0;
-                }
-
-                let index = (y * scaled_size + x) * 4;
-                texture_data[index] = 0; // R
-                texture_data[index + 1] = 0; // G
-                texture_data[index + 2] = 25; // B
-                texture_data[index + 3] = pixel_value; // A
+    commands.insert_resource(SplotchTextures::generate(&mut images));
+}
+
+/// Toon shaded Perlin blot, `texture_size` pixels square
+pub fn splotch_pixels (texture_size: usize, seed: u32) -> Vec<u8> {
+    let perlin = Perlin::new(seed);
+    let mut texture_data = vec![0u8; texture_size * texture_size * 4];
+    for y in 0..texture_size {
+        for x in 0..texture_size {
+            let value = perlin.get([5.0 * x as f64 / texture_size as f64, 10.0 * y as f64 / texture_size as f64]);
+            let x_weight = 1.0 - (2.0 * (x as f64 / texture_size as f64 - 0.5)).abs(); // linear 0.0 to 1.0 (0.0 at the edges, 1.0 in the middle)
+            let y_weight = 1.0 - (2.0 * (y as f64 / texture_size as f64 - 0.5)).abs();
+            let weight = x_weight * y_weight;
+            let value = (value + (weight * 1.5)) * weight;
+            let mut pixel_value = ((value * 1000.0).min(255.0).max(0.0)) as u8;
+
+            if pixel_value > 150 { // make toon
+                pixel_value = 200;
+            } else {
+                pixel_value = 0;
             }
+
+            let index = (y * texture_size + x) * 4;
+            texture_data[index] = 0; // R
+            texture_data[index + 1] = 0; // G
+            texture_data[index + 2] = 25; // B
+            texture_data[index + 3] = pixel_value; // A
         }
+    }
+    return texture_data;
+}
 
-        let splotch_image = Image::new_fill(
-            Extent3d {
-                width: scaled_size as u32,
-                height: scaled_size as u32,
-                depth_or_array_layers: 1,
-            },
-            TextureDimension::D2,
-            &texture_data,
-            TextureFormat::Rgba8UnormSrgb,
-            RenderAssetUsages::all()
-        );
+pub fn splotch_image (texture_size: usize, seed: u32) -> Image {
+    return Image::new_fill(
+        Extent3d {
+            width: texture_size as u32,
+            height: texture_size as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &splotch_pixels(texture_size, seed),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all()
+    );
+}
 
-        let splotch_image = images.add(splotch_image);
+pub fn manage_spawns (
+    mut commands: Commands,
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    mut splotch_pool: ResMut<SplotchPool>,
+    splotch_textures: Res<SplotchTextures>,
+) {
+    let mut rng = rand::thread_rng();
+    for splotch in splotch_registry.to_spawn.iter() {
+        let size = splotch.size;
+        let splotch_image = splotch_textures.get(size, rng.gen_range(0..SPLOTCH_VARIANTS));
 
-        commands.spawn((
+        // faded splotches are reused before new entities are spawned
+        let entity = match splotch_pool.free.pop() {
+            Some(entity) => entity,
+            None => commands.spawn_empty().id(),
+        };
+        let mut splotch_entity = commands.entity(entity);
+        splotch_entity.insert((
             Sprite {
                 image: splotch_image.clone(),
                 custom_size: Some(Vec2::new(size as f32, size as f32)),
+                flip_x: rng.gen_bool(0.5),
+                flip_y: rng.gen_bool(0.5),
                 ..default()
             },
             Transform::from_translation(splotch.position.extend(0.0)),
+            Visibility::Inherited,
             Splotch {
                 size,
                 timer: Timer::from_seconds(1., TimerMode::Repeating),
                 position: splotch.position,
                 image: splotch_image,
+                effect: splotch.effect,
             },
         ));
+        if let SplotchEffect::Cloud { lifetime } = splotch.effect {
+            let radius = size as f32 / 2.0;
+            splotch_entity.insert((
+                InkCloud {
+                    radius,
+                    timer: Timer::from_seconds(lifetime, TimerMode::Once),
+                },
+                Collider::ball(radius),
+                Sensor,
+            ));
+        }
     }
     splotch_registry.to_spawn.clear();
 }
 
-fn tick_splotch (
+/// Fade splotches, faded ones go back to the [`SplotchPool`] and leave the platform they stained
+pub fn tick_splotch (
     mut commands: Commands,
-    mut splotch_query: Query<(&mut Splotch, &Transform, &mut Sprite, Entity)>,
+    mut splotch_query: Query<(&mut Splotch, &mut Sprite, Entity, Option<&mut InkCloud>, Option<&InkStain>)>,
+    mut slick_query: Query<&mut SlickPlatform>,
+    mut splotch_pool: ResMut<SplotchPool>,
     time: Res<Time>,
-    mut images: ResMut<Assets<Image>>,
 ) {
-    for (mut splotch, _transform, mut sprite, entity) in splotch_query.iter_mut() {
+    for (mut splotch, mut sprite, entity, cloud, stain) in splotch_query.iter_mut() {
+        // clouds hold until their lifetime is up, then fade like any other splotch
+        if let Some(mut cloud) = cloud {
+            cloud.timer.tick(time.delta());
+            if !cloud.timer.finished() {continue}
+        }
         splotch.timer.tick(time.delta());
         if splotch.timer.finished() {
             let new_alpha = sprite.clone().color.alpha() * 0.75;
@@ -152,10 +311,147 @@ fn tick_splotch (
             splotch.timer.reset();
         }
         if sprite.color.alpha() < 0.1 {
-            images.remove(splotch.image.id()); // dealloc image
-            commands.entity(entity).despawn(); // kill entity
+            if let Some(mut slick) = stain.and_then(|stain| slick_query.get_mut(stain.platform).ok()) {
+                slick.stains = slick.stains.saturating_sub(1);
+            }
+            // hide and strip it, manage_spawns hands it out again
+            commands.entity(entity)
+                .remove::<(Splotch, InkCloud, InkStain, Collider, Sensor)>()
+                .insert(Visibility::Hidden);
+            splotch_pool.free.push(entity);
+        }
+    }
+}
 
+/// Stains and clouds spawned on or right next to a platform stain it, decorative splotches don't
+fn stain_platforms (
+    mut commands: Commands,
+    splotch_query: Query<(Entity, &Splotch), Added<Splotch>>,
+    mut platform_query: Query<(Option<&Friction>, Option<&mut SlickPlatform>), With<Platform>>,
+    rapier_context: ReadDefaultRapierContext,
+) {
+    if splotch_query.is_empty() {return}
+    let rapier_context = rapier_context.single();
+    let mut new_stains: HashMap<Entity, usize> = HashMap::new();
+    for (entity, splotch) in splotch_query.iter() {
+        let reach = match splotch.effect {
+            SplotchEffect::None => continue,
+            SplotchEffect::Stain => 8.0,
+            SplotchEffect::Cloud { .. } => splotch.size as f32 / 2.0,
+        };
+        let filter = QueryFilter::only_fixed().exclude_sensors();
+        let Some((platform, projection)) = rapier_context.project_point(splotch.position, true, filter) else {continue};
+        if !platform_query.contains(platform) {continue}
+        if projection.is_inside || projection.point.distance(splotch.position) <= reach {
+            commands.entity(entity).insert(InkStain { platform });
+            *new_stains.entry(platform).or_insert(0) += 1;
+        }
+    }
+    for (platform, count) in new_stains {
+        let Ok((friction, slick)) = platform_query.get_mut(platform) else {continue};
+        match slick {
+            Some(mut slick) => slick.stains += count,
+            None => {
+                commands.entity(platform).insert((
+                    SlickPlatform { original: friction.copied().unwrap_or_default(), stains: count },
+                    Friction {
+                        coefficient: 0.0,
+                        combine_rule: CoefficientCombineRule::Min,
+                    },
+                ));
+            },
         }
-        
     }
-}
\ No newline at end of file
+}
+
+/// Platforms whose last stain faded get their friction back
+fn restore_unstained_platforms (
+    mut commands: Commands,
+    platform_query: Query<(Entity, &SlickPlatform), Changed<SlickPlatform>>,
+) {
+    for (platform, slick) in platform_query.iter() {
+        if slick.stains > 0 {continue}
+        commands.entity(platform).insert(slick.original).remove::<SlickPlatform>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn cloud (lifetime: f32) -> InkCloud {
+        return InkCloud {
+            radius: 10.0,
+            timer: Timer::from_seconds(lifetime, TimerMode::Once),
+        };
+    }
+
+    #[test]
+    fn cloud_blocks_lines_through_it () {
+        let cloud = cloud(5.0);
+        assert!(cloud.blocks(Vec2::ZERO, Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0)));
+        assert!(cloud.blocks(Vec2::ZERO, Vec2::new(-50.0, 8.0), Vec2::new(50.0, 8.0)));
+        assert!(!cloud.blocks(Vec2::ZERO, Vec2::new(-50.0, 12.0), Vec2::new(50.0, 12.0)));
+    }
+
+    #[test]
+    fn cloud_only_blocks_between_the_points () {
+        let cloud = cloud(5.0);
+        // the cloud is past the end of the line
+        assert!(!cloud.blocks(Vec2::new(80.0, 0.0), Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0)));
+        assert!(cloud.blocks(Vec2::new(58.0, 0.0), Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0)));
+        // both points at the same spot
+        assert!(cloud.blocks(Vec2::ZERO, Vec2::new(5.0, 0.0), Vec2::new(5.0, 0.0)));
+        assert!(!cloud.blocks(Vec2::ZERO, Vec2::new(20.0, 0.0), Vec2::new(20.0, 0.0)));
+    }
+
+    #[test]
+    fn fading_cloud_blocks_nothing () {
+        let mut cloud = cloud(1.0);
+        cloud.timer.tick(Duration::from_secs_f32(0.5));
+        assert!(cloud.blocks(Vec2::ZERO, Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0)));
+        cloud.timer.tick(Duration::from_secs_f32(0.6));
+        assert!(!cloud.blocks(Vec2::ZERO, Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0)));
+    }
+
+    #[test]
+    fn platform_stays_slick_until_its_last_stain_fades () {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<SplotchPool>();
+        app.add_systems(Update, (tick_splotch, restore_unstained_platforms).chain());
+        let original = Friction::coefficient(0.8);
+        let platform = app.world_mut().spawn((
+            Platform::SOLID,
+            Friction::coefficient(0.0),
+            SlickPlatform { original, stains: 2 },
+        )).id();
+        let mut stain = |alpha: f32| app.world_mut().spawn((
+            Splotch {
+                size: 20,
+                timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                position: Vec2::ZERO,
+                image: Handle::default(),
+                effect: SplotchEffect::Stain,
+            },
+            Sprite {
+                color: Color::BLACK.with_alpha(alpha),
+                ..default()
+            },
+            InkStain { platform },
+        )).id();
+        let faded = stain(0.05);
+        let fresh = stain(1.0);
+
+        app.update();
+        assert_eq!(app.world().get::<SlickPlatform>(platform).map(|slick| slick.stains), Some(1));
+        assert_eq!(app.world().get::<Friction>(platform).unwrap().coefficient, 0.0);
+        assert!(app.world().get::<InkStain>(faded).is_none());
+
+        app.world_mut().get_mut::<Sprite>(fresh).unwrap().color.set_alpha(0.05);
+        app.update();
+        assert!(app.world().get::<SlickPlatform>(platform).is_none());
+        assert_eq!(app.world().get::<Friction>(platform).unwrap().coefficient, 0.8);
+    }
+}
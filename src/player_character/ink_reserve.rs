@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::player_character::*;
+
+/// Ink the squid spends on jumps, dashes and attacks, refills over time and from [`InkPickup`]s
+///
+/// Also home to the ink cloud action, the most expensive way to spend it
+pub struct InkReservePlugin;
+
+impl Plugin for InkReservePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (refill_ink, collect_ink_pickups, release_ink_cloud));
+    }
+}
+
+#[derive(Component)]
+pub struct InkReserve {
+    pub ink: f32,
+    pub max_ink: f32,
+    /// Seconds since ink was last spent, refilling waits for this
+    pub since_spent: f32,
+}
+
+impl Default for InkReserve {
+    fn default() -> Self {
+        return Self::new(100.0);
+    }
+}
+
+impl InkReserve {
+    pub fn new (max_ink: f32) -> Self {
+        Self {
+            ink: max_ink,
+            max_ink,
+            since_spent: 0.0,
+        }
+    }
+    /// Take `amount` if there is that much left, otherwise leave the reserve alone and return false
+    pub fn spend (&mut self, amount: f32) -> bool {
+        if self.ink < amount {return false}
+        self.ink -= amount;
+        self.since_spent = 0.0;
+        return true;
+    }
+    pub fn refill (&mut self, amount: f32) {
+        self.ink = (self.ink + amount).min(self.max_ink);
+    }
+    pub fn fraction (&self) -> f32 {
+        if self.max_ink <= 0.0 {return 0.0}
+        return self.ink / self.max_ink;
+    }
+}
+
+/// Refills the squid's ink when it swims through
+#[derive(Component)]
+pub struct InkPickup {
+    pub amount: f32,
+}
+
+pub fn spawn_ink_pickup (
+    commands: &mut Commands,
+    position: Vec2,
+    amount: f32,
+) {
+    commands.spawn((
+        Name::new("ink pickup"),
+        Sprite {
+            color: Color::srgb(0.1, 0.05, 0.3),
+            custom_size: Some(Vec2::new(12.0, 12.0)),
+            ..default()
+        },
+        Transform::from_translation(position.extend(1.0)),
+        Collider::ball(8.0),
+        Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        InkPickup { amount },
+    ));
+}
+
+fn refill_ink (
+    mut reserve_query: Query<(&Player, &mut InkReserve)>,
+    movement_config: Res<MovementConfig>,
+    time: Res<Time>,
+) {
+    for (player_struct, mut reserve) in reserve_query.iter_mut() {
+        let tuning = &movement_config.current(player_struct.swimming).ink;
+        reserve.since_spent += time.delta_secs();
+        if reserve.since_spent < tuning.refill_delay {continue}
+        // water refills faster
+        let rate = if player_struct.swimming {tuning.refill_rate * 2.0} else {tuning.refill_rate};
+        reserve.refill(rate * time.delta_secs());
+    }
+}
+
+fn collect_ink_pickups (
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut reserve_query: Query<&mut InkReserve>,
+    pickup_query: Query<&InkPickup>,
+) {
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(a, b, _) = collision_event else {continue};
+        let (pickup_entity, collector) = if pickup_query.contains(*a) {(*a, *b)} else if pickup_query.contains(*b) {(*b, *a)} else {continue};
+        let Ok(mut reserve) = reserve_query.get_mut(collector) else {continue};
+        let Ok(pickup) = pickup_query.get(pickup_entity) else {continue};
+        reserve.refill(pickup.amount);
+        commands.entity(pickup_entity).despawn_recursive();
+    }
+}
+
+fn release_ink_cloud (
+    mut player_query: Query<(&Player, &Transform, &mut InkReserve)>,
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+) {
+    let Ok((player_struct, transform, mut reserve)) = player_query.get_single_mut() else {return};
+    if !actions.just_pressed(PlayerAction::InkCloud) {return}
+    let tuning = &movement_config.current(player_struct.swimming).ink;
+    if !reserve.spend(tuning.cloud_cost) {return}
+    spawn_ink_cloud(&mut splotch_registry, tuning.cloud_size as usize, transform.translation.xy(), tuning.cloud_time);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spend_takes_ink_only_when_there_is_enough () {
+        let mut reserve = InkReserve::new(50.0);
+        reserve.since_spent = 3.0;
+        assert!(reserve.spend(20.0));
+        assert_eq!(reserve.ink, 30.0);
+        assert_eq!(reserve.since_spent, 0.0);
+
+        reserve.since_spent = 3.0;
+        assert!(!reserve.spend(40.0));
+        assert_eq!(reserve.ink, 30.0);
+        assert_eq!(reserve.since_spent, 3.0);
+        assert!(reserve.spend(30.0));
+        assert_eq!(reserve.ink, 0.0);
+    }
+
+    #[test]
+    fn refill_stops_at_the_maximum () {
+        let mut reserve = InkReserve::new(50.0);
+        reserve.spend(40.0);
+        reserve.refill(15.0);
+        assert_eq!(reserve.fraction(), 0.5);
+        reserve.refill(100.0);
+        assert_eq!(reserve.ink, 50.0);
+    }
+}
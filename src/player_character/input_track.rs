@@ -1,6 +1,5 @@
-use std::time::Instant;
-
 use bevy::prelude::*;
+use super::actions::*;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum InputDirection {
@@ -10,42 +9,149 @@ pub enum InputDirection {
     Down,
 }
 
+const DEFAULT_MAX_AGE: f32 = 2.0;
+const DEFAULT_MAX_LEN: usize = 16;
+
+/// Recent direction presses stamped with virtual time in seconds, oldest first
+///
+/// Virtual time stops while the game is paused and follows its speed, so timing checks
+/// behave the same under slow motion and fixed-step tests
 #[derive(Resource)]
 pub struct InputStack {
-    pub stack: Vec<(InputDirection, Instant)>,
+    pub stack: Vec<(InputDirection, f32)>,
+    /// Inputs older than this many seconds are dropped
+    pub max_age: f32,
+    /// Never keep more than this many inputs
+    pub max_len: usize,
+    now: f32,
+    total_pushed: u64,
 }
 
 impl InputStack {
     pub fn new () -> Self {
         Self {
             stack: Vec::new(),
+            max_age: DEFAULT_MAX_AGE,
+            max_len: DEFAULT_MAX_LEN,
+            now: 0.0,
+            total_pushed: 0,
         }
     }
     pub fn push (&mut self, direction: InputDirection) {
-        self.stack.push((direction, Instant::now()));
+        self.stack.push((direction, self.now));
+        self.total_pushed += 1;
+        self.evict();
     }
-    pub fn pop (&mut self) -> Option<(InputDirection, Instant)> {
+    pub fn pop (&mut self) -> Option<(InputDirection, f32)> {
         self.stack.pop()
     }
     pub fn clear (&mut self) {
         self.stack.clear();
     }
+    /// Advance the stack's clock and drop inputs that are too old
+    pub fn tick (&mut self, now: f32) {
+        self.now = now;
+        self.evict();
+    }
+    pub fn now (&self) -> f32 {
+        self.now
+    }
+    /// Keep inputs for at least `max_age` seconds and `max_len` presses, never shrinks below the defaults
+    pub fn keep_at_least (&mut self, max_age: f32, max_len: usize) {
+        self.max_age = max_age.max(DEFAULT_MAX_AGE);
+        self.max_len = max_len.max(DEFAULT_MAX_LEN);
+    }
+    /// A stack with each input pushed at its own time, then ticked to `now`
+    #[cfg(test)]
+    pub fn from_entries (inputs: &[(InputDirection, f32)], now: f32) -> Self {
+        let mut stack = Self::new();
+        for (direction, time) in inputs {
+            stack.tick(*time);
+            stack.push(direction.clone());
+        }
+        stack.tick(now);
+        return stack;
+    }
+    /// How many inputs have ever been pushed, evicted or not
+    pub fn total_pushed (&self) -> u64 {
+        self.total_pushed
+    }
+    fn evict (&mut self) {
+        let oldest_allowed = self.now - self.max_age;
+        self.stack.retain(|(_, time)| *time >= oldest_allowed);
+        if self.stack.len() > self.max_len {
+            let excess = self.stack.len() - self.max_len;
+            self.stack.drain(..excess);
+        }
+    }
+    /// Every input from the last `seconds`, newest first
+    pub fn within (&self, seconds: f32) -> impl Iterator<Item = &(InputDirection, f32)> {
+        let oldest_allowed = self.now - seconds;
+        self.stack.iter().rev().take_while(move |(_, time)| *time >= oldest_allowed)
+    }
+    /// The newest `count` inputs, newest first, if all of them happened in the last `seconds`
+    pub fn last_within (&self, count: usize, seconds: f32) -> Option<Vec<InputDirection>> {
+        let inputs = self.within(seconds).take(count).map(|(direction, _)| direction.clone()).collect::<Vec<InputDirection>>();
+        if inputs.len() < count {return None}
+        Some(inputs)
+    }
 }
 
 pub fn track_input (
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut input_stack: ResMut<InputStack>,
+    time: Res<Time<Virtual>>,
 ) {
-    if input.just_pressed(KeyCode::KeyA) {
+    input_stack.tick(time.elapsed_secs());
+    if actions.just_pressed(PlayerAction::MoveLeft) {
         input_stack.push(InputDirection::Left);
     }
-    if input.just_pressed(KeyCode::KeyD) {
+    if actions.just_pressed(PlayerAction::MoveRight) {
         input_stack.push(InputDirection::Right);
     }
-    if input.just_pressed(KeyCode::KeyW) {
+    if actions.just_pressed(PlayerAction::MoveUp) {
         input_stack.push(InputDirection::Up);
     }
-    if input.just_pressed(KeyCode::KeyS) {
+    if actions.just_pressed(PlayerAction::Dive) {
         input_stack.push(InputDirection::Down);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn within_returns_recent_inputs_newest_first () {
+        let stack = InputStack::from_entries(&[(InputDirection::Left, 0.0), (InputDirection::Right, 1.0), (InputDirection::Up, 1.5)], 2.0);
+        let directions = |seconds| stack.within(seconds).map(|(direction, _)| direction.clone()).collect::<Vec<InputDirection>>();
+        assert_eq!(directions(0.6), vec![InputDirection::Up]);
+        assert_eq!(directions(1.0), vec![InputDirection::Up, InputDirection::Right]);
+        assert_eq!(directions(2.0), vec![InputDirection::Up, InputDirection::Right, InputDirection::Left]);
+    }
+
+    #[test]
+    fn old_inputs_are_evicted () {
+        let stack = InputStack::from_entries(&[(InputDirection::Left, 0.0), (InputDirection::Right, 1.0), (InputDirection::Up, 1.5)], 3.25);
+        assert_eq!(stack.stack.len(), 1);
+        assert_eq!(stack.total_pushed(), 3);
+    }
+
+    #[test]
+    fn stack_never_grows_past_max_len () {
+        let mut stack = InputStack::new();
+        for _ in 0..stack.max_len + 4 {
+            stack.push(InputDirection::Down);
+        }
+        assert_eq!(stack.stack.len(), stack.max_len);
+        assert_eq!(stack.total_pushed(), stack.max_len as u64 + 4);
+    }
+
+    #[test]
+    fn last_within_needs_every_input_in_the_window () {
+        let stack = InputStack::from_entries(&[(InputDirection::Left, 0.0), (InputDirection::Right, 1.0), (InputDirection::Up, 1.5)], 2.0);
+        assert_eq!(stack.last_within(2, 1.0), Some(vec![InputDirection::Up, InputDirection::Right]));
+        assert_eq!(stack.last_within(3, 1.0), None);
+        assert_eq!(stack.last_within(3, 2.0), Some(vec![InputDirection::Up, InputDirection::Right, InputDirection::Left]));
+    }
+}
@@ -5,6 +5,18 @@ pub mod camera_tracking;
 pub mod base_movement;
 pub mod player_animation;
 pub mod ink;
+pub mod actions;
+pub mod combos;
+pub mod replay;
+pub mod movement_config;
+pub mod swimming;
+pub mod charge;
+pub mod tentacle;
+pub mod spin;
+pub mod wall;
+pub mod ink_reserve;
+#[cfg(test)]
+pub(crate) mod test_app;
 
 use player::*;
 use ui::*;
@@ -12,4 +24,13 @@ use input_track::*;
 use camera_tracking::*;
 use base_movement::*;
 use player_animation::*;
-use ink::*;
\ No newline at end of file
+use ink::*;
+use actions::*;
+use combos::*;
+use movement_config::*;
+use swimming::*;
+use charge::*;
+use tentacle::*;
+use spin::*;
+use wall::*;
+use ink_reserve::*;
\ No newline at end of file
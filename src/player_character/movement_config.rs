@@ -0,0 +1,491 @@
+use bevy::prelude::*;
+use bevy::asset::io::file::FileAssetReader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "hot_reload")]
+use crate::flex_load::FileWatcher;
+
+pub struct MovementConfigPlugin;
+
+impl Plugin for MovementConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MovementConfig::load());
+        #[cfg(feature = "hot_reload")]
+        {
+            app.insert_resource(MovementConfigWatcher(FileWatcher::new(movement_config_file())));
+            app.add_systems(Update, reload_changed_movement_config);
+        }
+    }
+}
+
+/// Movement tuning file, relative to the folder the game runs from
+pub const MOVEMENT_CONFIG_PATH: &str = "config/movement.ron";
+
+/// Preset used when the file doesn't name one, or names one that doesn't exist
+pub const DEFAULT_PRESET: &str = "land";
+
+/// Preset used for swimming when the file doesn't name one
+pub const DEFAULT_SWIM_PRESET: &str = "underwater";
+
+fn movement_config_file() -> PathBuf {
+    return FileAssetReader::get_base_path().join(MOVEMENT_CONFIG_PATH);
+}
+
+/// Every number that decides how the squid moves, one set per preset
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MovementPreset {
+    /// Horizontal speed at full input, pixels per second
+    pub run_speed: f32,
+    /// Fraction of horizontal speed kept after one second without input
+    pub damping: f32,
+    pub jump_speed: f32,
+    /// Seconds after leaving the ground that a jump still works
+    #[serde(default = "default_coyote_time")]
+    pub coyote_time: f32,
+    /// Seconds before landing that a jump press is remembered
+    #[serde(default = "default_jump_buffer")]
+    pub jump_buffer: f32,
+    /// Seconds passthrough platforms stay solid-free after dive and jump drops through one
+    #[serde(default = "default_drop_through_time")]
+    pub drop_through_time: f32,
+    /// Gravity scale while jump is held and the squid is still rising
+    pub rise_gravity: f32,
+    pub fall_gravity: f32,
+    /// Gravity scale while dive is held
+    pub dive_gravity: f32,
+    pub dash_speed: f32,
+    pub dash_up_speed: f32,
+    pub dash_down_speed: f32,
+    /// Seconds before another dash can start
+    pub dash_duration: f32,
+    /// Swimming, only read from the swim preset
+    #[serde(default)]
+    pub swim: SwimTuning,
+    #[serde(default)]
+    pub charge: ChargeTuning,
+    #[serde(default)]
+    pub tentacle: TentacleTuning,
+    #[serde(default)]
+    pub spin: SpinTuning,
+    #[serde(default)]
+    pub wall: WallTuning,
+    #[serde(default)]
+    pub ink: InkTuning,
+}
+
+/// What moves cost from the ink reserve, and how fast it comes back
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InkTuning {
+    /// Ink per second once refilling starts, doubled in water
+    pub refill_rate: f32,
+    /// Seconds after spending before refilling starts
+    pub refill_delay: f32,
+    pub jump_cost: f32,
+    /// Share of jump speed left when there isn't enough ink to jump
+    pub dry_jump: f32,
+    pub dash_cost: f32,
+    pub jet_cost: f32,
+    pub charge_cost: f32,
+    pub spin_cost: f32,
+    pub tentacle_cost: f32,
+    pub cloud_cost: f32,
+    /// Diameter of an ink cloud, pixels
+    pub cloud_size: f32,
+    /// Seconds an ink cloud hides things before it starts fading
+    pub cloud_time: f32,
+    /// Damping used instead of the preset's while standing on an ink stained platform
+    pub slick_damping: f32,
+}
+
+impl Default for InkTuning {
+    fn default() -> Self {
+        Self {
+            refill_rate: 20.0,
+            refill_delay: 0.6,
+            jump_cost: 4.0,
+            dry_jump: 0.7,
+            dash_cost: 20.0,
+            jet_cost: 15.0,
+            charge_cost: 25.0,
+            spin_cost: 20.0,
+            tentacle_cost: 10.0,
+            cloud_cost: 30.0,
+            cloud_size: 120.0,
+            cloud_time: 4.0,
+            slick_damping: 0.7,
+        }
+    }
+}
+
+/// Sliding down and jumping off climbable walls
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WallTuning {
+    /// Fastest fall while touching a wall
+    pub slide_speed: f32,
+    /// Fastest fall while pushing into a wall
+    pub cling_speed: f32,
+    /// Push away from the wall and up on a wall jump
+    pub jump_away: f32,
+    pub jump_up: f32,
+    /// Seconds after a wall jump before steering works again
+    pub jump_lock: f32,
+}
+
+impl Default for WallTuning {
+    fn default() -> Self {
+        Self {
+            slide_speed: 120.0,
+            cling_speed: 25.0,
+            jump_away: 260.0,
+            jump_up: 320.0,
+            jump_lock: 0.2,
+        }
+    }
+}
+
+/// Spin attack and the dizziness after it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpinTuning {
+    /// Seconds the spin hitbox is out
+    pub duration: f32,
+    pub radius: f32,
+    pub damage: f32,
+    /// Seconds of dizziness after a spin, this is the spin's cooldown
+    pub dizzy_time: f32,
+    /// Share of run speed kept while dizzy
+    pub dizzy_speed: f32,
+}
+
+impl Default for SpinTuning {
+    fn default() -> Self {
+        Self {
+            duration: 0.4,
+            radius: 48.0,
+            damage: 15.0,
+            dizzy_time: 1.2,
+            dizzy_speed: 0.5,
+        }
+    }
+}
+
+/// Tentacle grapple and grab
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TentacleTuning {
+    /// Furthest the tentacle reaches, pixels
+    pub range: f32,
+    /// Seconds after letting go before it can fire again
+    pub cooldown: f32,
+    /// How fast the rope shortens while swinging, pixels per second
+    pub reel_speed: f32,
+    /// Shortest the rope gets
+    pub min_length: f32,
+    /// Speed grabbed objects are pulled in at
+    pub pull_speed: f32,
+}
+
+impl Default for TentacleTuning {
+    fn default() -> Self {
+        Self {
+            range: 300.0,
+            cooldown: 0.8,
+            reel_speed: 150.0,
+            min_length: 40.0,
+            pull_speed: 350.0,
+        }
+    }
+}
+
+/// Hold to charge, release to launch
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChargeTuning {
+    /// Seconds to reach a full charge
+    pub full_charge_time: f32,
+    /// Launch speed with no charge and with a full charge
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// Damage dealt with no charge and with a full charge
+    pub min_damage: f32,
+    pub max_damage: f32,
+    /// Seconds the hitbox stays out after launching
+    pub launch_time: f32,
+    pub hitbox_radius: f32,
+}
+
+impl Default for ChargeTuning {
+    fn default() -> Self {
+        Self {
+            full_charge_time: 1.0,
+            min_speed: 300.0,
+            max_speed: 900.0,
+            min_damage: 10.0,
+            max_damage: 40.0,
+            launch_time: 0.35,
+            hitbox_radius: 28.0,
+        }
+    }
+}
+
+/// How the squid moves inside a water volume
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwimTuning {
+    /// Share of gravity cancelled by the water, 1 floats in place
+    pub buoyancy: f32,
+    /// Fraction of speed kept after one second, lower is thicker water
+    pub drag: f32,
+    /// Acceleration from holding a direction, pixels per second squared
+    pub thrust: f32,
+    /// Speed thrust alone can reach
+    pub max_speed: f32,
+    /// Speed of an ink jet burst
+    pub jet_speed: f32,
+    /// Seconds between ink jets
+    pub jet_cooldown: f32,
+}
+
+impl Default for SwimTuning {
+    fn default() -> Self {
+        Self {
+            buoyancy: 0.9,
+            drag: 0.3,
+            thrust: 600.0,
+            max_speed: 160.0,
+            jet_speed: 420.0,
+            jet_cooldown: 0.6,
+        }
+    }
+}
+
+impl Default for MovementPreset {
+    fn default() -> Self {
+        Self {
+            run_speed: 170.0,
+            damping: 0.05,
+            jump_speed: 300.0,
+            coyote_time: default_coyote_time(),
+            jump_buffer: default_jump_buffer(),
+            drop_through_time: default_drop_through_time(),
+            rise_gravity: 0.7,
+            fall_gravity: 1.3,
+            dive_gravity: 2.5,
+            dash_speed: 500.0,
+            dash_up_speed: 500.0,
+            dash_down_speed: 800.0,
+            dash_duration: 0.5,
+            swim: SwimTuning::default(),
+            charge: ChargeTuning::default(),
+            tentacle: TentacleTuning::default(),
+            spin: SpinTuning::default(),
+            wall: WallTuning::default(),
+            ink: InkTuning::default(),
+        }
+    }
+}
+
+fn default_coyote_time() -> f32 {
+    return 0.1;
+}
+fn default_jump_buffer() -> f32 {
+    return 0.1;
+}
+fn default_drop_through_time() -> f32 {
+    return 0.3;
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct MovementConfig {
+    /// Name of the preset in use
+    #[serde(default = "default_preset_name")]
+    pub active: String,
+    /// Preset used while the squid is in a water volume
+    #[serde(default = "default_swim_preset_name")]
+    pub swim_preset: String,
+    pub presets: HashMap<String, MovementPreset>,
+    /// Handed out when a named preset is missing from the file
+    #[serde(skip)]
+    fallback: MovementPreset,
+}
+
+fn default_preset_name() -> String {
+    return DEFAULT_PRESET.to_string();
+}
+fn default_swim_preset_name() -> String {
+    return DEFAULT_SWIM_PRESET.to_string();
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            active: default_preset_name(),
+            swim_preset: default_swim_preset_name(),
+            presets: HashMap::from([(default_preset_name(), MovementPreset::default())]),
+            fallback: MovementPreset::default(),
+        }
+    }
+}
+
+impl MovementConfig {
+    /// Read the movement file, falling back to the defaults if it is missing or invalid
+    pub fn load() -> Self {
+        return Self::load_from(&movement_config_file());
+    }
+    fn load_from(path: &Path) -> Self {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("could not read movement config {}, using defaults: {}", path.display(), error);
+                return Self::default();
+            }
+        };
+        match Self::parse(&contents) {
+            Ok(config) => return config,
+            Err(error) => {
+                error!("could not parse movement config {}, using defaults: {}", path.display(), error);
+                return Self::default();
+            }
+        }
+    }
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let config = ron::de::from_str::<Self>(contents).map_err(|error| error.to_string())?;
+        if !config.presets.contains_key(&config.active) {
+            warn!("movement preset \"{}\" does not exist, using the defaults", config.active);
+        }
+        return Ok(config);
+    }
+    /// The preset in use, the built in defaults if it is missing from the file
+    pub fn preset(&self) -> &MovementPreset {
+        return self.presets.get(&self.active).unwrap_or(&self.fallback);
+    }
+    /// The preset used while swimming, the built in defaults if it is missing from the file
+    pub fn swim_preset(&self) -> &MovementPreset {
+        return self.presets.get(&self.swim_preset).unwrap_or(&self.fallback);
+    }
+    /// The swim preset in water, the active preset everywhere else
+    pub fn current(&self, swimming: bool) -> &MovementPreset {
+        if swimming {
+            return self.swim_preset();
+        }
+        return self.preset();
+    }
+    /// Switch to another preset, returns false and keeps the current one if `name` doesn't exist
+    pub fn set_preset(&mut self, name: &str) -> bool {
+        if !self.presets.contains_key(name) {return false}
+        self.active = name.to_string();
+        return true;
+    }
+    pub fn preset_names(&self) -> Vec<&str> {
+        let mut names = self.presets.keys().map(|name| name.as_str()).collect::<Vec<&str>>();
+        names.sort();
+        return names;
+    }
+}
+
+#[cfg(feature = "hot_reload")]
+#[derive(Resource)]
+struct MovementConfigWatcher(FileWatcher);
+
+/// Re-read the movement file when it changes
+#[cfg(feature = "hot_reload")]
+fn reload_changed_movement_config (
+    mut watcher: ResMut<MovementConfigWatcher>,
+    mut config: ResMut<MovementConfig>,
+    time: Res<Time<Real>>,
+) {
+    if !watcher.0.changed(time.delta()) {return}
+
+    let contents = match std::fs::read_to_string(watcher.0.path()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            error!("movement config not reloaded: {}", error);
+            return;
+        }
+    };
+    match MovementConfig::parse(&contents) {
+        Ok(reloaded) => {
+            *config = reloaded;
+            info!("reloaded movement config, using preset \"{}\"", config.active);
+        },
+        Err(error) => error!("movement config not reloaded: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_PRESETS: &str = r#"(
+        active: "floaty",
+        presets: {
+            "land": (run_speed: 170.0, damping: 0.05, jump_speed: 300.0, rise_gravity: 0.7, fall_gravity: 1.3, dive_gravity: 2.5,
+                dash_speed: 500.0, dash_up_speed: 500.0, dash_down_speed: 800.0, dash_duration: 0.5),
+            "floaty": (run_speed: 120.0, damping: 0.2, jump_speed: 350.0, coyote_time: 0.2, rise_gravity: 0.4, fall_gravity: 0.8, dive_gravity: 2.0,
+                dash_speed: 400.0, dash_up_speed: 450.0, dash_down_speed: 600.0, dash_duration: 0.7, spin: (dizzy_time: 2.0)),
+        },
+    )"#;
+
+    /// Write a movement file under the temp folder, removed again by the caller
+    fn temp_config (name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sdk_reborn_{}_{}.ron", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        return path;
+    }
+
+    #[test]
+    fn parses_presets_and_fills_in_missing_tuning () {
+        let config = MovementConfig::parse(TWO_PRESETS).unwrap();
+        assert_eq!(config.preset_names(), vec!["floaty", "land"]);
+        let floaty = config.preset();
+        assert_eq!(floaty.run_speed, 120.0);
+        assert_eq!(floaty.coyote_time, 0.2);
+        assert_eq!(floaty.jump_buffer, default_jump_buffer());
+        assert_eq!(floaty.spin.dizzy_time, 2.0);
+        assert_eq!(floaty.spin.radius, SpinTuning::default().radius);
+        // no swim preset in the file, swimming uses the defaults
+        assert_eq!(config.swim_preset, DEFAULT_SWIM_PRESET);
+        assert_eq!(config.current(true).run_speed, MovementPreset::default().run_speed);
+    }
+
+    #[test]
+    fn selects_presets_by_name () {
+        let mut config = MovementConfig::parse(TWO_PRESETS).unwrap();
+        assert!(config.set_preset("land"));
+        assert_eq!(config.preset().run_speed, 170.0);
+        assert!(!config.set_preset("icy"));
+        assert_eq!(config.active, "land");
+
+        // a file naming a preset it doesn't have falls back to the defaults
+        let config = MovementConfig::parse(&TWO_PRESETS.replace("active: \"floaty\"", "active: \"icy\"")).unwrap();
+        assert_eq!(config.preset().jump_speed, MovementPreset::default().jump_speed);
+    }
+
+    #[test]
+    fn missing_or_invalid_file_uses_the_defaults () {
+        let missing = std::env::temp_dir().join("sdk_reborn_no_such_movement.ron");
+        let config = MovementConfig::load_from(&missing);
+        assert_eq!(config.active, DEFAULT_PRESET);
+        assert_eq!(config.preset().jump_speed, MovementPreset::default().jump_speed);
+
+        let invalid = temp_config("invalid_movement", "(active: \"land\", presets: {\"land\": (run_speed: fast)})");
+        let config = MovementConfig::load_from(&invalid);
+        std::fs::remove_file(&invalid).unwrap();
+        assert_eq!(config.preset_names(), vec![DEFAULT_PRESET]);
+
+        let valid = temp_config("valid_movement", TWO_PRESETS);
+        let config = MovementConfig::load_from(&valid);
+        std::fs::remove_file(&valid).unwrap();
+        assert_eq!(config.active, "floaty");
+    }
+
+    #[test]
+    fn shipped_movement_file_parses () {
+        let contents = std::fs::read_to_string(movement_config_file()).expect("config/movement.ron should exist");
+        let config = MovementConfig::parse(&contents).expect("config/movement.ron should parse");
+        assert!(config.presets.contains_key(&config.active));
+    }
+}
@@ -2,12 +2,13 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use crate::{flex_load::*, PLAYER_Z};
 use crate::player_character::*;
+use std::collections::HashSet;
 
 pub struct SquidPlugin;
 
 impl Plugin for SquidPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((PlayerUIPlugin, CameraTrackingPlugin, BaseMovementPlugin, PlayerAnimationPlugin, InkPlugin));
+        app.add_plugins((ActionsPlugin, CombosPlugin, MovementConfigPlugin, SwimmingPlugin, ChargePlugin, TentaclePlugin, SpinPlugin, WallPlugin, InkReservePlugin, PlayerUIPlugin, CameraTrackingPlugin, BaseMovementPlugin, PlayerAnimationPlugin, InkPlugin));
         app.insert_resource(InputStack::new());
         app.add_systems(OnEnter(AssetLoadState::Ready), spawn_squid);
         app.add_systems(Update, track_input.run_if(in_state(AssetLoadState::Ready)));
@@ -19,6 +20,20 @@ impl Plugin for SquidPlugin {
 pub struct Player {
     pub grounded: bool,
     pub has_jump: bool,
+    /// Inside a water volume, see swimming.rs
+    pub swimming: bool,
+    /// Climbable wall the squid is sliding down, see wall.rs
+    pub wall: Option<WallSide>,
+    /// Seconds left before steering works again after a wall jump
+    pub wall_jump_lock: f32,
+    /// Seconds left to jump after walking off a ledge
+    pub coyote_time_left: f32,
+    /// Seconds left for an early jump press to fire on landing
+    pub jump_buffer_left: f32,
+    /// This frame's jump press was already used, by a jump or a drop, later systems must not use it again
+    pub jump_used: bool,
+    /// Surface normal under the squid, straight up while airborne
+    pub ground_normal: Vec2,
     pub health: Health,
 }
 
@@ -27,12 +42,29 @@ impl Player {
         Self {
             grounded: false,
             has_jump: false,
+            swimming: false,
+            wall: None,
+            wall_jump_lock: 0.0,
+            coyote_time_left: 0.0,
+            jump_buffer_left: 0.0,
+            jump_used: false,
+            ground_normal: Vec2::Y,
             health: Health::new(100.0),
         }
     }
 }
 
 
+/// Sensor under the squid, tracks every platform it overlaps
+#[derive(Component, Default)]
+pub struct FeetSensor {
+    pub contacts: HashSet<Entity>,
+}
+
+/// Legs sprite drawn under the squid's head
+#[derive(Component, Default)]
+pub struct SquidLegs;
+
 #[derive(Component, Default)]
 pub struct Health {
     pub health: f32,
@@ -66,23 +98,23 @@ impl Health {
 fn spawn_squid (
     mut commands: Commands,
     loaded: Res<LoadedAssets>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
-    let squid_map_layout = texture_atlas_layouts.add(
-        TextureAtlasLayout::from_grid(UVec2::splat(32), 4, 4, None, None)
-    );
+    let squid_map = match loaded.get_atlas("squid_map") {
+        Ok(atlas) => atlas,
+        Err(error) => {
+            error!("could not spawn the squid: {}", error);
+            return;
+        }
+    };
     
     commands.spawn((
         Sprite {
-            image: loaded.get_typed_clone::<Image>("squid_map").unwrap(),
+            image: squid_map.image.clone(),
             custom_size: Some(Vec2::new(64.0, 64.0)),
-            texture_atlas: 
-                Some( TextureAtlas {
-                    layout: squid_map_layout.clone(),
-                    index: 4,
-                }),
+            texture_atlas: Some(squid_map.texture_atlas(4)),
             ..default()
         },
+        Transform::from_translation(Vec3::new(0.0, 0.0, PLAYER_Z)),
         PlayerAnimation::default(),
         RigidBody::Dynamic,
         Collider::capsule_y(3., 20.),
@@ -95,21 +127,20 @@ fn spawn_squid (
         },
         LockedAxes::ROTATION_LOCKED,
         Player::new(),
+        InkReserve::default(),
     )).with_children(|parent| {
-        parent.spawn(
+        parent.spawn((
             Sprite {
-                image: loaded.get_typed_clone::<Image>("squid_map").unwrap(),
+                image: squid_map.image.clone(),
                 custom_size: Some(Vec2::new(64.0, 64.0)),
-                texture_atlas: 
-                    Some( TextureAtlas {
-                        layout: squid_map_layout.clone(),
-                        index: 2,
-                    }),
+                texture_atlas: Some(squid_map.texture_atlas(2)),
                 ..default()
-            }
-        );
+            },
+            SquidLegs,
+        ));
         parent.spawn((
             Name::new("feet"),
+            FeetSensor::default(),
             Transform::from_translation(Vec3::new(0.0, -10.0, 0.0)),
             Collider::ball(16.),
             Sensor,
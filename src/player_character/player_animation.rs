@@ -17,6 +17,16 @@ pub enum AnimState {
     Jump,
     Fall,
     Dash,
+    /// Swimming with a direction held
+    Swim,
+    /// Floating in water
+    SwimIdle,
+    /// Ink jet burst underwater
+    Jet,
+    /// Spin attack
+    Spin,
+    /// After a spin, see spin.rs
+    Dizzy,
 }
 
 #[derive(Default, PartialEq, Eq)]
@@ -54,6 +64,10 @@ impl PlayerAnimation {
         if self.state == state {return}
         self.change_state(state);
     }
+    /// Spinning and dizzy are set by spin.rs and can't be replaced by movement states
+    pub fn is_busy (&self) -> bool {
+        return self.state == AnimState::Spin || self.state == AnimState::Dizzy;
+    }
 }
 
 // Squid layout:
@@ -74,14 +88,14 @@ impl PlayerAnimation {
 // 14: leg jump
 
 fn animate_squid (
-    mut player_query: Query<(&Player, &Velocity, &mut PlayerAnimation, &mut Sprite, &Children)>,
-    mut sprite_query: Query<&mut Sprite, Without<Player>>,
+    mut player_query: Query<(&Player, &Velocity, &mut PlayerAnimation, &mut Sprite)>,
+    mut legs_query: Query<&mut Sprite, (With<SquidLegs>, Without<Player>)>,
     dash_timer: Res<DashTimer>,
     time: Res<Time>,
 ) {
     if player_query.iter().count() == 0 {return}
-    let (player_struct, player_velocity, mut player_anim, mut head_sprite, player_children) = player_query.single_mut();
-    let mut leg_sprite = sprite_query.get_mut(player_children[0]).unwrap();
+    let (player_struct, player_velocity, mut player_anim, mut head_sprite) = player_query.single_mut();
+    let Ok(mut leg_sprite) = legs_query.get_single_mut() else {return};
     player_anim.timer.tick(time.delta());
     if player_anim.timer.finished() {
         player_anim.frame += 1;
@@ -98,7 +112,7 @@ fn animate_squid (
     }
 
     // determine if falling
-    if player_anim.state != AnimState::Dash && !player_struct.grounded {
+    if !player_anim.is_busy() && player_anim.state != AnimState::Dash && !player_struct.grounded && !player_struct.swimming {
         if player_velocity.linvel.y > 0.0 {
             player_anim.set_state(AnimState::Jump);
         } else if player_velocity.linvel.y < -20.0 {
@@ -148,6 +162,29 @@ fn animate_squid (
         AnimState::Jump => {
             head_sprite.texture_atlas.as_mut().unwrap().index = 13;
             leg_sprite.texture_atlas.as_mut().unwrap().index = 14;
+        },
+        AnimState::Swim => {
+            head_sprite.texture_atlas.as_mut().unwrap().index = 4;
+            leg_sprite.texture_atlas.as_mut().unwrap().index = player_anim.frame % 2 + 2;
+        },
+        AnimState::SwimIdle => { // slow leg stroke while floating
+            head_sprite.texture_atlas.as_mut().unwrap().index = 4;
+            leg_sprite.texture_atlas.as_mut().unwrap().index = (player_anim.frame / 3) % 2 + 2;
+        },
+        AnimState::Jet => {
+            head_sprite.texture_atlas.as_mut().unwrap().index = player_anim.frame % 2 + 9;
+            leg_sprite.texture_atlas.as_mut().unwrap().index = player_anim.frame % 2 + 9;
+        },
+        AnimState::Spin => { // turn around every frame
+            let flip = player_anim.frame % 2 == 0;
+            head_sprite.flip_x = flip;
+            leg_sprite.flip_x = flip;
+            head_sprite.texture_atlas.as_mut().unwrap().index = 4;
+            leg_sprite.texture_atlas.as_mut().unwrap().index = 2;
+        },
+        AnimState::Dizzy => { // angry head, legs wobble, sway and stars are in spin.rs
+            head_sprite.texture_atlas.as_mut().unwrap().index = 8;
+            leg_sprite.texture_atlas.as_mut().unwrap().index = player_anim.frame % 2 + 2;
         }
     }
 }
\ No newline at end of file
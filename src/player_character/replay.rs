@@ -0,0 +1,338 @@
+use bevy::prelude::*;
+use bevy::input::InputSystem;
+use bevy::time::{TimeSystem, TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::flex_load::*;
+use super::actions::*;
+
+/// Records the player's actions to a file, or plays a recording back instead of the keyboard and gamepads
+///
+/// Every frame stores the held actions, the movement axis and the frame delta. Playback feeds them into
+/// [`ActionState`], the keys bound to those actions into `ButtonInput<KeyCode>` and the delta into bevy's
+/// clock, so [`InputStack`](super::input_track::InputStack), the combos and the movement systems see
+/// exactly what they saw when the run was recorded. This works the same in a headless app, use
+/// [`InputReplayMode::ReplayRecording`] to play a recording built in code.
+pub struct InputReplayPlugin {
+    pub mode: InputReplayMode,
+}
+
+#[derive(Clone)]
+pub enum InputReplayMode {
+    Off,
+    Record(PathBuf),
+    Replay(PathBuf),
+    ReplayRecording(InputRecording),
+}
+
+impl InputReplayPlugin {
+    /// Read the mode from the command line, `--record <file>` or `--replay <file>`
+    pub fn from_args() -> Self {
+        let args = std::env::args().collect::<Vec<String>>();
+        let value_after = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|index| args.get(index + 1)).map(PathBuf::from);
+        let mode = if let Some(path) = value_after("--replay") {
+            InputReplayMode::Replay(path)
+        } else if let Some(path) = value_after("--record") {
+            InputReplayMode::Record(path)
+        } else {
+            InputReplayMode::Off
+        };
+        return Self { mode };
+    }
+}
+
+impl Plugin for InputReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.mode {
+            InputReplayMode::Off => {},
+            InputReplayMode::Record(path) => {
+                app.insert_resource(InputRecorder {
+                    path: path.clone(),
+                    recording: InputRecording::default(),
+                });
+                app.add_systems(Last, (record_frame.run_if(in_state(AssetLoadState::Ready)), save_recording_on_exit).chain());
+            },
+            InputReplayMode::Replay(path) => {
+                match InputRecording::load(path) {
+                    Ok(recording) => add_playback(app, recording),
+                    Err(error) => error!("could not load input recording {}: {}", path.display(), error),
+                }
+            },
+            InputReplayMode::ReplayRecording(recording) => add_playback(app, recording.clone()),
+        }
+    }
+}
+
+fn add_playback(app: &mut App, recording: InputRecording) {
+    info!("replaying {} recorded frames", recording.frames.len());
+    app.insert_resource(InputPlayback {
+        recording,
+        next_frame: 0,
+        held_keys: Vec::new(),
+    });
+    app.configure_sets(PreUpdate, ReadDevices.run_if(not(resource_exists::<InputPlayback>)));
+    app.add_systems(First, set_playback_delta.before(TimeSystem).run_if(resource_exists::<InputPlayback>.and(gameplay_frame_ahead)));
+    app.add_systems(PreUpdate, play_frame.after(InputSystem).run_if(resource_exists::<InputPlayback>.and(gameplay_frame_ahead)));
+}
+
+/// True in `First` and `PreUpdate` of every frame whose `Update` runs in [`AssetLoadState::Ready`]
+///
+/// Loading takes a different number of frames every run, so recordings start when gameplay does. The
+/// recorder runs in `Last` and checks the state itself, but the state changes after `PreUpdate`, so
+/// playback also has to count the frame where the change to `Ready` is still pending.
+fn gameplay_frame_ahead (
+    state: Res<State<AssetLoadState>>,
+    next_state: Res<NextState<AssetLoadState>>,
+) -> bool {
+    return *state.get() == AssetLoadState::Ready || matches!(*next_state, NextState::Pending(AssetLoadState::Ready));
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Real time since the previous frame
+    pub delta: Duration,
+    pub held: Vec<PlayerAction>,
+    pub move_axis: (f32, f32),
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        return ron::de::from_str::<Self>(&contents).map_err(|error| error.to_string());
+    }
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string(self).map_err(|error| error.to_string())?;
+        return std::fs::write(path, contents).map_err(|error| error.to_string());
+    }
+}
+
+#[derive(Resource)]
+pub struct InputRecorder {
+    pub path: PathBuf,
+    pub recording: InputRecording,
+}
+
+/// Present while a recording is playing, removed once the last frame has been played
+#[derive(Resource)]
+pub struct InputPlayback {
+    pub recording: InputRecording,
+    pub next_frame: usize,
+    /// Keys pressed for the previous frame
+    pub held_keys: Vec<KeyCode>,
+}
+
+fn record_frame (
+    mut recorder: ResMut<InputRecorder>,
+    actions: Res<ActionState>,
+    time: Res<Time<Real>>,
+) {
+    let move_axis = actions.move_axis();
+    recorder.recording.frames.push(RecordedFrame {
+        delta: time.delta(),
+        held: PlayerAction::ALL.into_iter().filter(|action| actions.pressed(*action)).collect(),
+        move_axis: (move_axis.x, move_axis.y),
+    });
+}
+
+fn save_recording_on_exit (
+    mut exit_events: EventReader<AppExit>,
+    recorder: Res<InputRecorder>,
+) {
+    if exit_events.read().next().is_none() {return}
+    match recorder.recording.save(&recorder.path) {
+        Ok(()) => info!("saved {} frames of input to {}", recorder.recording.frames.len(), recorder.path.display()),
+        Err(error) => error!("could not save input recording {}: {}", recorder.path.display(), error),
+    }
+}
+
+fn set_playback_delta (
+    playback: Res<InputPlayback>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if let Some(frame) = playback.recording.frames.get(playback.next_frame) {
+        *strategy = TimeUpdateStrategy::ManualDuration(frame.delta);
+    }
+}
+
+fn play_frame (
+    mut commands: Commands,
+    mut playback: ResMut<InputPlayback>,
+    mut actions: ResMut<ActionState>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    keys: Option<ResMut<ButtonInput<KeyCode>>>,
+    bindings: Res<InputBindings>,
+) {
+    let Some(frame) = playback.recording.frames.get(playback.next_frame).cloned() else {
+        info!("input replay finished");
+        // the devices take over next frame, presses from the last recorded frame shouldn't fire again
+        actions.clear_edges();
+        *strategy = TimeUpdateStrategy::Automatic;
+        commands.remove_resource::<InputPlayback>();
+        return;
+    };
+    actions.clear_edges();
+    for action in PlayerAction::ALL {
+        actions.set(action, frame.held.contains(&action));
+    }
+    actions.set_move_axis(Vec2::new(frame.move_axis.0, frame.move_axis.1));
+
+    let held_keys = frame.held.iter().filter_map(|action| bindings.keys_for(*action).first().copied()).collect::<Vec<KeyCode>>();
+    if let Some(mut keys) = keys {
+        press_recorded_keys(&mut keys, &playback.held_keys, &held_keys);
+    }
+    playback.held_keys = held_keys;
+    playback.next_frame += 1;
+}
+
+/// Replace whatever the keyboard did this frame with the recorded keys, edges worked out from `previous`
+fn press_recorded_keys (
+    keys: &mut ButtonInput<KeyCode>,
+    previous: &[KeyCode],
+    held: &[KeyCode],
+) {
+    keys.reset_all();
+    for key in previous {
+        keys.press(*key);
+    }
+    keys.clear();
+    for key in previous.iter().filter(|key| !held.contains(key)) {
+        keys.release(*key);
+    }
+    for key in held {
+        keys.press(*key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy_rapier2d::prelude::*;
+    use crate::player_character::{base_movement::*, combos::*, input_track::*, ink_reserve::*, movement_config::*, player::*, test_app};
+
+    /// Frames where the change to `Ready` is requested, replays must not depend on it
+    #[derive(Resource)]
+    struct LoadingFrames(u32);
+
+    /// Every frame is a whole number of 1/64 s, so virtual time adds up exactly whatever the loading took
+    fn ticks (count: u64) -> Duration {
+        return Duration::from_nanos(15_625_000 * count);
+    }
+
+    /// Walk right, double tap right to dash, jump, then dash left with the button
+    fn scripted_actions (frame: u32) -> Vec<PlayerAction> {
+        match frame {
+            5..12 | 16..20 => return vec![PlayerAction::MoveRight],
+            30..36 => return vec![PlayerAction::Jump],
+            50..54 => return vec![PlayerAction::MoveLeft, PlayerAction::Dash],
+            _ => return vec![],
+        }
+    }
+
+    /// Stands in for the keyboard while recording
+    fn drive_actions (
+        mut actions: ResMut<ActionState>,
+        mut frame: Local<u32>,
+    ) {
+        let held = scripted_actions(*frame);
+        actions.clear_edges();
+        for action in PlayerAction::ALL {
+            actions.set(action, held.contains(&action));
+        }
+        let mut axis = Vec2::ZERO;
+        if actions.pressed(PlayerAction::MoveLeft) {axis.x -= 1.0}
+        if actions.pressed(PlayerAction::MoveRight) {axis.x += 1.0}
+        actions.set_move_axis(axis);
+        *frame += 1;
+    }
+
+    fn finish_loading (
+        mut loading: ResMut<LoadingFrames>,
+        mut next_state: ResMut<NextState<AssetLoadState>>,
+    ) {
+        loading.0 = loading.0.saturating_sub(1);
+        if loading.0 == 0 {
+            next_state.set(AssetLoadState::Ready);
+        }
+    }
+
+    fn squid_app (mode: InputReplayMode, loading_frames: u32) -> App {
+        let mut app = test_app::squid_app();
+        app.add_plugins(StatesPlugin);
+        app.init_state::<AssetLoadState>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.insert_resource(InputBindings::default());
+        app.insert_resource(InputStack::new());
+        app.insert_resource(DashTimer::new(MovementPreset::default().dash_duration));
+        app.insert_resource(ComboRegistry::with_dashes(MovementPreset::default().dash_duration));
+        app.add_event::<ComboPerformed>();
+        app.insert_resource(LoadingFrames(loading_frames));
+        app.add_systems(PreUpdate, drive_actions.in_set(ReadDevices));
+        app.add_systems(Update, finish_loading.run_if(in_state(AssetLoadState::Loading)));
+        app.add_systems(Update, (track_input, detect_combos, drop_through_platforms, control_squid, manage_dash, tick_dash_timer).chain().run_if(in_state(AssetLoadState::Ready)));
+        app.add_plugins(InputReplayPlugin { mode });
+        let squid = test_app::spawn_squid(&mut app);
+        app.world_mut().entity_mut(squid).insert((
+            Player {
+                grounded: true,
+                has_jump: true,
+                ..Player::new()
+            },
+            GravityScale(1.0),
+        ));
+        return app;
+    }
+
+    /// Everything the movement systems decide, or None while still loading
+    fn snapshot (app: &mut App) -> Option<String> {
+        let world = app.world_mut();
+        if *world.resource::<State<AssetLoadState>>().get() != AssetLoadState::Ready {return None}
+        let mut player_query = world.query::<(&Player, &Velocity, &GravityScale, &InkReserve)>();
+        let (player, velocity, gravity, ink_reserve) = player_query.single(world);
+        let dash_timer = world.resource::<DashTimer>();
+        return Some(format!(
+            "{} {} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+            player.grounded, player.has_jump, player.coyote_time_left, player.jump_buffer_left,
+            velocity.linvel, gravity.0, ink_reserve.ink,
+            dash_timer.timer.elapsed(), dash_timer.direction, world.resource::<InputStack>().total_pushed(),
+        ));
+    }
+
+    #[test]
+    fn replay_matches_recorded_run () {
+        let path = std::env::temp_dir().join("sdk_reborn_replay_test.ron");
+        let mut recording_app = squid_app(InputReplayMode::Record(path), 3);
+        let mut recorded = Vec::new();
+        for frame in 0..80 {
+            recording_app.insert_resource(TimeUpdateStrategy::ManualDuration(ticks(1 + frame % 3)));
+            recording_app.update();
+            recorded.extend(snapshot(&mut recording_app));
+        }
+        let recording = recording_app.world().resource::<InputRecorder>().recording.clone();
+        assert_eq!(recording.frames.len(), recorded.len());
+        assert!(recorded.first() != recorded.last(), "the scripted run should move the squid");
+
+        // loading takes longer and runs at a different rate this time
+        let mut replay_app = squid_app(InputReplayMode::ReplayRecording(recording), 7);
+        replay_app.insert_resource(TimeUpdateStrategy::ManualDuration(ticks(4)));
+        let mut replayed = Vec::new();
+        let mut jump_key_pressed = false;
+        for _ in 0..7 + recorded.len() {
+            replay_app.update();
+            replayed.extend(snapshot(&mut replay_app));
+            let jump_held = replay_app.world().resource::<ActionState>().pressed(PlayerAction::Jump);
+            let space_held = replay_app.world().resource::<ButtonInput<KeyCode>>().pressed(KeyCode::Space);
+            assert_eq!(jump_held, space_held);
+            jump_key_pressed |= space_held;
+        }
+        assert!(jump_key_pressed);
+        assert_eq!(replay_app.world().resource::<InputPlayback>().next_frame, recorded.len());
+        assert_eq!(replayed, recorded);
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy_rapier2d::prelude::*;
+use std::collections::HashSet;
+use crate::player_character::*;
+
+/// Spin attack, hits everything around the squid and leaves it [`Dizzy`]
+///
+/// The dizziness is the spin's cooldown, the squid can't spin, dash or move at full speed until it wears off
+pub struct SpinPlugin;
+
+impl Plugin for SpinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (start_spin, end_spin, wear_off_dizziness, sway_dizzy_squid).chain().after(MovementSystems));
+    }
+}
+
+#[derive(Component)]
+pub struct Spinning {
+    pub timer: Timer,
+}
+
+#[derive(Component)]
+pub struct Dizzy {
+    pub timer: Timer,
+}
+
+/// Star circling the head of a dizzy squid
+#[derive(Component)]
+struct DizzyStar {
+    offset: f32,
+}
+
+const DIZZY_STARS: usize = 3;
+
+fn start_spin (
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &Player, &mut PlayerAnimation, &mut InkReserve), (Without<Spinning>, Without<Dizzy>)>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+) {
+    let Ok((player_entity, player_struct, mut player_anim, mut ink_reserve)) = player_query.get_single_mut() else {return};
+    if !actions.just_pressed(PlayerAction::Spin) {return}
+    let preset = movement_config.current(player_struct.swimming);
+    if !ink_reserve.spend(preset.ink.spin_cost) {return}
+    let tuning = &preset.spin;
+
+    player_anim.change_state(AnimState::Spin);
+    commands.entity(player_entity).insert(Spinning {
+        timer: Timer::from_seconds(tuning.duration, TimerMode::Once),
+    }).with_children(|parent| {
+        parent.spawn((
+            Name::new("spin hitbox"),
+            Collider::ball(tuning.radius),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            ChargeHitbox {
+                damage: tuning.damage,
+                timer: Timer::from_seconds(tuning.duration, TimerMode::Once),
+                hit: HashSet::new(),
+            },
+        ));
+    });
+}
+
+fn end_spin (
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &Player, &mut Spinning, &mut PlayerAnimation)>,
+    movement_config: Res<MovementConfig>,
+    time: Res<Time>,
+) {
+    let Ok((player_entity, player_struct, mut spinning, mut player_anim)) = player_query.get_single_mut() else {return};
+    spinning.timer.tick(time.delta());
+    if !spinning.timer.finished() {return}
+    let tuning = &movement_config.current(player_struct.swimming).spin;
+
+    player_anim.change_state(AnimState::Dizzy);
+    commands.entity(player_entity).remove::<Spinning>().insert(Dizzy {
+        timer: Timer::from_seconds(tuning.dizzy_time, TimerMode::Once),
+    }).with_children(|parent| {
+        for i in 0..DIZZY_STARS {
+            parent.spawn((
+                Name::new("dizzy star"),
+                Sprite {
+                    color: Color::srgb(1.0, 0.9, 0.3),
+                    custom_size: Some(Vec2::new(5.0, 5.0)),
+                    ..default()
+                },
+                Transform::from_translation(Vec3::new(0.0, 30.0, 1.0)),
+                DizzyStar {
+                    offset: i as f32 * std::f32::consts::TAU / DIZZY_STARS as f32,
+                },
+            ));
+        }
+    });
+}
+
+fn wear_off_dizziness (
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Dizzy, &mut PlayerAnimation, &mut Sprite)>,
+    star_query: Query<Entity, With<DizzyStar>>,
+    time: Res<Time>,
+) {
+    let Ok((player_entity, mut dizzy, mut player_anim, mut head_sprite)) = player_query.get_single_mut() else {return};
+    dizzy.timer.tick(time.delta());
+    if !dizzy.timer.finished() {return}
+
+    player_anim.change_state(AnimState::Idle);
+    head_sprite.anchor = Anchor::Center;
+    commands.entity(player_entity).remove::<Dizzy>();
+    for star in star_query.iter() {
+        commands.entity(star).despawn_recursive();
+    }
+}
+
+/// Stars circle over the head while it sways side to side
+fn sway_dizzy_squid (
+    mut player_query: Query<(&Dizzy, &mut Sprite), With<Player>>,
+    mut star_query: Query<(&DizzyStar, &mut Transform)>,
+) {
+    let Ok((dizzy, mut head_sprite)) = player_query.get_single_mut() else {return};
+    let elapsed = dizzy.timer.elapsed_secs();
+    head_sprite.anchor = Anchor::Custom(Vec2::new((elapsed * 8.0).sin() * 0.06, 0.0));
+    for (star, mut transform) in star_query.iter_mut() {
+        let angle = elapsed * 5.0 + star.offset;
+        transform.translation.x = angle.cos() * 14.0;
+        transform.translation.y = 30.0 + angle.sin() * 4.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_character::test_app::*;
+
+    fn spin_app () -> (App, Entity) {
+        let mut app = squid_app();
+        app.add_plugins(SpinPlugin);
+        set_frame_time(&mut app, 0.05);
+        let squid = spawn_squid(&mut app);
+        return (app, squid);
+    }
+
+    #[test]
+    fn spin_costs_ink_then_leaves_the_squid_dizzy () {
+        let (mut app, squid) = spin_app();
+        let tuning = SpinTuning::default();
+        hold(&mut app, PlayerAction::Spin, true);
+        app.update();
+        hold(&mut app, PlayerAction::Spin, false);
+        assert!(app.world().get::<Spinning>(squid).is_some());
+        assert_eq!(app.world().get::<InkReserve>(squid).unwrap().ink, 100.0 - InkTuning::default().spin_cost);
+        let started = app.world().resource::<Time>().elapsed_secs();
+
+        let mut dizzy_at = None;
+        let mut recovered_at = None;
+        for _ in 0..100 {
+            app.update();
+            let now = app.world().resource::<Time>().elapsed_secs();
+            let dizzy = app.world().get::<Dizzy>(squid).is_some();
+            if dizzy && dizzy_at.is_none() {
+                dizzy_at = Some(now);
+                // spinning again while dizzy does nothing
+                hold(&mut app, PlayerAction::Spin, true);
+            } else {
+                hold(&mut app, PlayerAction::Spin, false);
+            }
+            if !dizzy && dizzy_at.is_some() {
+                recovered_at = Some(now);
+                break;
+            }
+        }
+        let dizzy_at = dizzy_at.expect("the spin should end in dizziness");
+        let recovered_at = recovered_at.expect("the dizziness should wear off");
+        assert!((dizzy_at - started - tuning.duration).abs() < 0.06);
+        assert!((recovered_at - dizzy_at - tuning.dizzy_time).abs() < 0.06);
+        assert!(app.world().get::<Spinning>(squid).is_none());
+        assert_eq!(app.world().get::<InkReserve>(squid).unwrap().ink, 100.0 - InkTuning::default().spin_cost);
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::{player_character::*, scenes::*};
+
+/// Swim mode, used instead of walking while the squid is inside a [`WaterVolume`]
+pub struct SwimmingPlugin;
+
+impl Plugin for SwimmingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InkJetTimer::new(SwimTuning::default().jet_cooldown));
+        app.add_systems(Update, (update_swimming, swim).chain());
+    }
+}
+
+#[derive(Resource)]
+pub struct InkJetTimer {
+    pub timer: Timer,
+}
+
+impl InkJetTimer {
+    pub fn new (duration: f32) -> Self {
+        let mut new_timer = Timer::from_seconds(duration, TimerMode::Once);
+        new_timer.set_elapsed(std::time::Duration::from_secs_f32(duration));
+        Self {
+            timer: new_timer,
+        }
+    }
+    /// True for the first part of a jet, while the squid is still being pushed
+    pub fn jetting (&self) -> bool {
+        return !self.timer.finished() && self.timer.elapsed_secs() < 0.25;
+    }
+}
+
+/// Switch between walking and swimming as the squid enters and leaves water
+fn update_swimming (
+    mut player_query: Query<(&mut Player, &Transform)>,
+    water_query: Query<(&WaterVolume, &Transform), Without<Player>>,
+) {
+    let Ok((mut player_struct, player_transform)) = player_query.get_single_mut() else {return};
+    let position = player_transform.translation.xy();
+    let in_water = water_query.iter().any(|(volume, transform)| volume.contains(transform.translation.xy(), position));
+    if in_water == player_struct.swimming {return}
+
+    player_struct.swimming = in_water;
+    player_struct.grounded = false;
+    player_struct.has_jump = false;
+}
+
+fn swim (
+    mut player_query: Query<(&Player, &mut Velocity, &mut GravityScale, &mut PlayerAnimation, &Transform, &mut InkReserve, Has<Dizzy>)>,
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    mut jet_timer: ResMut<InkJetTimer>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+    time: Res<Time>,
+) {
+    let Ok((player_struct, mut velocity, mut gravity, mut player_anim, transform, mut ink_reserve, dizzy)) = player_query.get_single_mut() else {return};
+    if !player_struct.swimming {return}
+    let preset = movement_config.swim_preset();
+    let tuning = &preset.swim;
+    let thrust = if dizzy {tuning.thrust * preset.spin.dizzy_speed} else {tuning.thrust};
+    jet_timer.timer.tick(time.delta());
+
+    gravity.0 = 1.0 - tuning.buoyancy;
+    velocity.linvel *= tuning.drag.powf(time.delta_secs());
+
+    // keys give eight directions, a stick gives any angle
+    let direction = actions.move_axis().clamp_length_max(1.0);
+    if direction.x < 0.0 {
+        player_anim.face = PlayerFace::Left;
+    } else if direction.x > 0.0 {
+        player_anim.face = PlayerFace::Right;
+    }
+    if direction != Vec2::ZERO {
+        // thrust can't push past max speed, but doesn't cut a jet short either
+        let limit = tuning.max_speed.max(velocity.linvel.length());
+        velocity.linvel = (velocity.linvel + direction * thrust * time.delta_secs()).clamp_length_max(limit);
+    }
+
+    // jump becomes an ink jet, it goes the way the squid is pushed or facing
+    if actions.just_pressed(PlayerAction::Jump) && jet_timer.timer.finished() && ink_reserve.spend(preset.ink.jet_cost) {
+        let jet_direction = if direction != Vec2::ZERO {
+            direction.normalize()
+        } else if player_anim.face == PlayerFace::Left {
+            Vec2::NEG_X
+        } else {
+            Vec2::X
+        };
+        velocity.linvel = jet_direction * tuning.jet_speed;
+        jet_timer.timer.set_duration(std::time::Duration::from_secs_f32(tuning.jet_cooldown));
+        jet_timer.timer.reset();
+        spawn_splotch_cluster(&mut splotch_registry, 60, transform.translation.xy() - jet_direction * 20.0);
+    }
+
+    if player_anim.is_busy() {return} // spinning or dizzy, see spin.rs
+    if jet_timer.jetting() {
+        player_anim.set_state(AnimState::Jet);
+    } else if direction != Vec2::ZERO {
+        player_anim.set_state(AnimState::Swim);
+    } else {
+        player_anim.set_state(AnimState::SwimIdle);
+    }
+}
@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::player_character::*;
+
+/// Tentacle fired toward the aim direction with the grab action
+///
+/// It latches onto the first collider in range. [`Grappleable`] surfaces tie the squid to them with
+/// a rope joint it can swing on and reel in, [`Grabbable`] bodies are pulled toward the squid.
+/// Anything else, or nothing in range, misses. Firing costs ink, letting go starts the cooldown.
+pub struct TentaclePlugin;
+
+impl Plugin for TentaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tentacle>();
+        app.add_systems(Update, (fire_tentacle, hold_tentacle, draw_tentacle).chain());
+    }
+}
+
+/// Surface the squid can swing from
+#[derive(Component, Default)]
+pub struct Grappleable;
+
+/// Light object or enemy the tentacle pulls toward the squid, needs a [`Velocity`]
+#[derive(Component, Default)]
+pub struct Grabbable;
+
+/// On a [`Grabbable`] body while the tentacle pulls it, its own movement should leave its velocity alone
+#[derive(Component, Default)]
+pub struct Grabbed;
+
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum TentacleState {
+    #[default] Idle,
+    /// Roped to a surface, the anchor is in the target's local space
+    Swinging { target: Entity, anchor: Vec2, length: f32 },
+    /// Pulling a grabbed body in
+    Pulling { target: Entity },
+}
+
+#[derive(Resource)]
+pub struct Tentacle {
+    pub state: TentacleState,
+    pub cooldown: Timer,
+}
+
+impl Default for Tentacle {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(TentacleTuning::default().cooldown, TimerMode::Once);
+        cooldown.set_elapsed(cooldown.duration());
+        Self {
+            state: TentacleState::Idle,
+            cooldown,
+        }
+    }
+}
+
+/// Stretched between the squid and whatever the tentacle holds
+#[derive(Component)]
+struct TentacleSprite;
+
+/// The way the squid is pushed, or up and forward when nothing is held
+fn aim_direction (actions: &ActionState, face: &PlayerFace) -> Vec2 {
+    let axis = actions.move_axis();
+    if axis != Vec2::ZERO {
+        return axis.normalize();
+    }
+    if *face == PlayerFace::Left {
+        return Vec2::new(-1.0, 1.0).normalize();
+    }
+    return Vec2::new(1.0, 1.0).normalize();
+}
+
+fn fire_tentacle (
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &Player, &PlayerAnimation, &Transform, &mut InkReserve), Without<Dizzy>>,
+    target_query: Query<(Has<Grappleable>, Has<Grabbable>, &GlobalTransform)>,
+    mut tentacle: ResMut<Tentacle>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+    rapier_context: ReadDefaultRapierContext,
+    time: Res<Time>,
+) {
+    tentacle.cooldown.tick(time.delta());
+    let Ok((player_entity, player_struct, player_anim, transform, mut ink_reserve)) = player_query.get_single_mut() else {return};
+    if tentacle.state != TentacleState::Idle || !tentacle.cooldown.finished() {return}
+    if !actions.just_pressed(PlayerAction::Grab) {return}
+    let preset = movement_config.current(player_struct.swimming);
+    // a miss costs the ink too
+    if !ink_reserve.spend(preset.ink.tentacle_cost) {return}
+    let tuning = &preset.tentacle;
+
+    let origin = transform.translation.xy();
+    let direction = aim_direction(&actions, &player_anim.face);
+    let filter = QueryFilter::default().exclude_rigid_body(player_entity).exclude_sensors();
+    let Some((hit_entity, distance)) = rapier_context.single().cast_ray(origin, direction, tuning.range, true, filter) else {
+        tentacle.cooldown.reset();
+        return;
+    };
+    let Ok((grappleable, grabbable, target_transform)) = target_query.get(hit_entity) else {
+        tentacle.cooldown.reset();
+        return;
+    };
+
+    if grappleable {
+        let hit_point = origin + direction * distance;
+        let anchor = hit_point - target_transform.translation().xy();
+        commands.entity(player_entity).insert(ImpulseJoint::new(hit_entity, RopeJointBuilder::new(distance).local_anchor1(anchor)));
+        tentacle.state = TentacleState::Swinging { target: hit_entity, anchor, length: distance };
+    } else if grabbable {
+        commands.entity(hit_entity).insert(Grabbed);
+        tentacle.state = TentacleState::Pulling { target: hit_entity };
+    } else {
+        tentacle.cooldown.reset();
+        return;
+    }
+    commands.spawn((
+        Name::new("tentacle"),
+        Sprite {
+            color: Color::srgb(0.85, 0.45, 0.6),
+            custom_size: Some(Vec2::new(distance, 4.0)),
+            ..default()
+        },
+        Transform::from_translation(origin.extend(transform.translation.z - 0.1)),
+        TentacleSprite,
+    ));
+}
+
+fn hold_tentacle (
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &Player, &Transform, Option<&mut ImpulseJoint>)>,
+    mut target_query: Query<(&GlobalTransform, Option<&mut Velocity>), Without<Player>>,
+    sprite_query: Query<Entity, With<TentacleSprite>>,
+    mut tentacle: ResMut<Tentacle>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+    time: Res<Time>,
+) {
+    if tentacle.state == TentacleState::Idle {return}
+    let Ok((player_entity, player_struct, transform, joint)) = player_query.get_single_mut() else {return};
+    let tuning = &movement_config.current(player_struct.swimming).tentacle;
+
+    let mut release = !actions.pressed(PlayerAction::Grab);
+    match tentacle.state {
+        TentacleState::Swinging { target, anchor, length } => {
+            if target_query.get(target).is_err() {
+                release = true;
+            } else if actions.move_axis().y > 0.0 && length > tuning.min_length {
+                // reel in by shortening the rope
+                let length = (length - tuning.reel_speed * time.delta_secs()).max(tuning.min_length);
+                if let Some(mut joint) = joint {
+                    *joint = ImpulseJoint::new(target, RopeJointBuilder::new(length).local_anchor1(anchor));
+                }
+                tentacle.state = TentacleState::Swinging { target, anchor, length };
+            }
+        },
+        TentacleState::Pulling { target } => {
+            match target_query.get_mut(target) {
+                Ok((target_transform, Some(mut velocity))) => {
+                    let offset = transform.translation.xy() - target_transform.translation().xy();
+                    if offset.length() < tuning.min_length {
+                        release = true;
+                    } else {
+                        velocity.linvel = offset.normalize() * tuning.pull_speed;
+                    }
+                },
+                _ => release = true,
+            }
+        },
+        TentacleState::Idle => {},
+    }
+
+    if !release {return}
+    commands.entity(player_entity).remove::<ImpulseJoint>();
+    if let TentacleState::Pulling { target } = tentacle.state {
+        if let Some(mut target) = commands.get_entity(target) {
+            target.remove::<Grabbed>();
+        }
+    }
+    for entity in sprite_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    tentacle.state = TentacleState::Idle;
+    tentacle.cooldown.set_duration(std::time::Duration::from_secs_f32(tuning.cooldown));
+    tentacle.cooldown.reset();
+}
+
+fn draw_tentacle (
+    mut sprite_query: Query<(&mut Sprite, &mut Transform), With<TentacleSprite>>,
+    player_query: Query<&Transform, (With<Player>, Without<TentacleSprite>)>,
+    target_query: Query<&GlobalTransform>,
+    tentacle: Res<Tentacle>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {return};
+    let end = match tentacle.state {
+        TentacleState::Swinging { target, anchor, .. } => target_query.get(target).map(|target| target.translation().xy() + anchor),
+        TentacleState::Pulling { target } => target_query.get(target).map(|target| target.translation().xy()),
+        TentacleState::Idle => return,
+    };
+    let Ok(end) = end else {return};
+    let start = player_transform.translation.xy();
+    let span = end - start;
+    for (mut sprite, mut transform) in sprite_query.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(span.length(), 4.0));
+        transform.translation = ((start + end) / 2.0).extend(player_transform.translation.z - 0.1);
+        transform.rotation = Quat::from_rotation_z(span.to_angle());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_character::test_app::*;
+
+    /// A squid aiming right at a grappleable wall `distance` away, with the physics for the ray cast
+    fn tentacle_app (distance: f32) -> (App, Entity, Entity) {
+        let mut app = squid_app();
+        app.add_plugins((TransformPlugin, HierarchyPlugin, AssetPlugin::default()));
+        app.init_asset::<Mesh>();
+        app.add_plugins((RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(64.0), TentaclePlugin));
+        set_frame_time(&mut app, 1.0 / 60.0);
+        let squid = spawn_squid(&mut app);
+        app.world_mut().entity_mut(squid).insert((RigidBody::Dynamic, GravityScale(0.0), Collider::ball(16.), PlayerAnimation::default()));
+        // the wall's face is `distance` from the squid's center
+        let wall = app.world_mut().spawn((Transform::from_xyz(distance + 16.0, 0.0, 0.0), RigidBody::Fixed, Collider::cuboid(16., 200.), Grappleable)).id();
+        set_move_axis(&mut app, Vec2::X);
+        // one step so the ray cast sees the colliders
+        app.update();
+        return (app, squid, wall);
+    }
+
+    fn ink (app: &App, squid: Entity) -> f32 {
+        return app.world().get::<InkReserve>(squid).unwrap().ink;
+    }
+
+    #[test]
+    fn tentacle_ropes_the_squid_to_a_wall_in_range_until_let_go () {
+        let (mut app, squid, wall) = tentacle_app(200.0);
+        hold(&mut app, PlayerAction::Grab, true);
+        app.update();
+        let joint = app.world().get::<ImpulseJoint>(squid).expect("the tentacle should have latched on");
+        assert_eq!(joint.parent, wall);
+        let TentacleState::Swinging { target, length, .. } = app.world().resource::<Tentacle>().state else {panic!("not swinging")};
+        assert_eq!(target, wall);
+        assert!((length - 200.0).abs() < 1.0);
+        assert_eq!(ink(&app, squid), 100.0 - InkTuning::default().tentacle_cost);
+
+        hold(&mut app, PlayerAction::Grab, false);
+        app.update();
+        assert!(app.world().get::<ImpulseJoint>(squid).is_none());
+        assert!(app.world().resource::<Tentacle>().state == TentacleState::Idle);
+        assert!(!app.world().resource::<Tentacle>().cooldown.finished());
+    }
+
+    #[test]
+    fn tentacle_misses_a_wall_out_of_range () {
+        let (mut app, squid, _) = tentacle_app(TentacleTuning::default().range + 50.0);
+        hold(&mut app, PlayerAction::Grab, true);
+        app.update();
+        assert!(app.world().get::<ImpulseJoint>(squid).is_none());
+        assert!(app.world().resource::<Tentacle>().state == TentacleState::Idle);
+        assert!(!app.world().resource::<Tentacle>().cooldown.finished());
+    }
+
+    #[test]
+    fn no_tentacle_without_ink () {
+        let (mut app, squid, _) = tentacle_app(200.0);
+        app.world_mut().get_mut::<InkReserve>(squid).unwrap().ink = InkTuning::default().tentacle_cost - 1.0;
+        hold(&mut app, PlayerAction::Grab, true);
+        app.update();
+        assert!(app.world().get::<ImpulseJoint>(squid).is_none());
+        assert!(app.world().resource::<Tentacle>().state == TentacleState::Idle);
+        // nothing was fired, so no cooldown either
+        assert!(app.world().resource::<Tentacle>().cooldown.finished());
+    }
+}
@@ -0,0 +1,38 @@
+//! Headless app and input helpers shared by the squid's system tests
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+use crate::player_character::*;
+
+/// An app with the resources the squid's systems read, tests add the systems they drive
+pub fn squid_app () -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.init_resource::<ActionState>();
+    app.insert_resource(MovementConfig::default());
+    app.init_resource::<SplotchRegistry>();
+    return app;
+}
+
+/// Advance time by the same step every update, the first update still has no delta
+pub fn set_frame_time (app: &mut App, seconds: f32) {
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(seconds)));
+}
+
+/// A squid with a full ink reserve, standing still in the air
+pub fn spawn_squid (app: &mut App) -> Entity {
+    return app.world_mut().spawn((Player::new(), Velocity::default(), Transform::default(), InkReserve::default())).id();
+}
+
+/// Hold or release an action for the next update, last update's press and release edges are forgotten
+pub fn hold (app: &mut App, action: PlayerAction, held: bool) {
+    let mut actions = app.world_mut().resource_mut::<ActionState>();
+    actions.clear_edges();
+    actions.set(action, held);
+}
+
+pub fn set_move_axis (app: &mut App, axis: Vec2) {
+    app.world_mut().resource_mut::<ActionState>().set_move_axis(axis);
+}
@@ -2,13 +2,14 @@ use bevy::prelude::*;
 use super::player::*;
 use crate::flex_load::*;
 use super::input_track::*;
+use super::ink_reserve::*;
 
 pub struct PlayerUIPlugin;
 
 impl Plugin for PlayerUIPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AssetLoadState::Ready), setup);
-        app.add_systems(Update, (update_input_stack, update_health_bar).run_if(in_state(AssetLoadState::Ready)));
+        app.add_systems(Update, (update_input_stack, update_health_bar, update_ink_meter).run_if(in_state(AssetLoadState::Ready)));
     }
 }
 
@@ -18,6 +19,9 @@ struct HealthBar;
 #[derive(Component)]
 struct InputStackText;
 
+#[derive(Component)]
+struct InkMeter;
+
 fn setup (
     mut commands: Commands,
 ) {
@@ -32,6 +36,17 @@ fn setup (
         },
         HealthBar,
     ));
+    commands.spawn((
+        Text("Ink: X/X".to_string()),
+        TextColor(Color::srgb(0.6, 0.5, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(200.0),
+            top: Val::Px(20.0),
+            ..default()
+        },
+        InkMeter,
+    ));
     commands.spawn((
         Text("Input Stack: X".to_string()),
         TextColor(Color::WHITE),
@@ -73,6 +88,18 @@ fn update_health_bar (
     }
 }
 
+fn update_ink_meter (
+    mut query: Query<&mut Text, With<InkMeter>>,
+    reserve_query: Query<&InkReserve, With<Player>>,
+) {
+    let Ok(reserve) = reserve_query.get_single() else {return};
+    for mut text in query.iter_mut() {
+        // ten cells, one per tenth of the reserve
+        let filled = (reserve.fraction() * 10.0).ceil() as usize;
+        text.0 = format!("Ink: {}{} {}/{}", "#".repeat(filled), "-".repeat(10 - filled.min(10)), reserve.ink as u32, reserve.max_ink as u32);
+    }
+}
+
 fn enum_to_arrow (direction: InputDirection) -> String {
     match direction {
         InputDirection::Up => "^".to_string(),
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::collections::HashSet;
+use crate::player_character::*;
+use rand::*;
+
+/// Sliding down, clinging to and jumping off [`Climbable`] surfaces
+///
+/// Each side of the squid has a sensor that tracks the climbable colliders it overlaps,
+/// the same way [`FeetSensor`] tracks platforms.
+pub struct WallPlugin;
+
+impl Plugin for WallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            spawn_wall_sensors,
+            (manage_wall_sensors, wall_slide).chain().after(MovementSystems),
+        ));
+    }
+}
+
+/// Surface the squid can slide down and jump off
+#[derive(Component, Default)]
+pub struct Climbable;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WallSide {
+    Left,
+    Right,
+}
+
+impl WallSide {
+    /// -1 for left, 1 for right
+    pub fn sign (&self) -> f32 {
+        match self {
+            WallSide::Left => return -1.0,
+            WallSide::Right => return 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct WallSensor {
+    pub side: WallSide,
+    pub contacts: HashSet<Entity>,
+}
+
+fn spawn_wall_sensors (
+    mut commands: Commands,
+    player_query: Query<Entity, Added<Player>>,
+) {
+    for player_entity in player_query.iter() {
+        commands.entity(player_entity).with_children(|parent| {
+            for side in [WallSide::Left, WallSide::Right] {
+                parent.spawn((
+                    Name::new("wall sensor"),
+                    Transform::from_translation(Vec3::new(side.sign() * 20.0, 0.0, 0.0)),
+                    Collider::cuboid(3.0, 10.0),
+                    Sensor,
+                    ActiveEvents::COLLISION_EVENTS,
+                    WallSensor {
+                        side,
+                        contacts: HashSet::new(),
+                    },
+                ));
+            }
+        });
+    }
+}
+
+fn manage_wall_sensors (
+    mut sensor_query: Query<(Entity, &mut WallSensor)>,
+    climbable_query: Query<(), With<Climbable>>,
+    mut collision_events: EventReader<CollisionEvent>,
+) {
+    for collision_event in collision_events.read() {
+        let (a, b, started) = match collision_event {
+            CollisionEvent::Started(a, b, _) => (*a, *b, true),
+            CollisionEvent::Stopped(a, b, _) => (*a, *b, false),
+        };
+        for (sensor_entity, mut sensor) in sensor_query.iter_mut() {
+            let wall = if a == sensor_entity {b} else if b == sensor_entity {a} else {continue};
+            if !climbable_query.contains(wall) {continue}
+            if started {
+                sensor.contacts.insert(wall);
+            } else {
+                sensor.contacts.remove(&wall);
+            }
+        }
+    }
+    for (_, mut sensor) in sensor_query.iter_mut() {
+        sensor.contacts.retain(|wall| climbable_query.contains(*wall));
+    }
+}
+
+fn wall_slide (
+    mut player_query: Query<(&mut Player, &mut Velocity, &mut PlayerAnimation, &Transform, &mut InkReserve)>,
+    sensor_query: Query<&WallSensor>,
+    mut splotch_registry: ResMut<SplotchRegistry>,
+    actions: Res<ActionState>,
+    movement_config: Res<MovementConfig>,
+) {
+    let Ok((mut player_struct, mut velocity, mut player_anim, transform, mut ink_reserve)) = player_query.get_single_mut() else {return};
+    let wall = sensor_query.iter().find(|sensor| !sensor.contacts.is_empty()).map(|sensor| sensor.side);
+    player_struct.wall = if player_struct.grounded || player_struct.swimming {None} else {wall};
+    let Some(side) = player_struct.wall else {return};
+    let preset = movement_config.preset();
+    let tuning = &preset.wall;
+    let wall_point = transform.translation.xy() + Vec2::new(side.sign() * 22.0, 0.0);
+
+    // a coyote or buffered jump from control_squid already used this frame's press
+    if actions.just_pressed(PlayerAction::Jump) && !player_struct.jump_used {
+        // a dry wall jump is weaker, like a dry jump
+        let inked = ink_reserve.spend(preset.ink.jump_cost);
+        let strength = if inked {1.0} else {preset.ink.dry_jump};
+        velocity.linvel = Vec2::new(-side.sign() * tuning.jump_away, tuning.jump_up) * strength;
+        player_struct.wall_jump_lock = tuning.jump_lock;
+        player_struct.jump_buffer_left = 0.0;
+        player_struct.jump_used = true;
+        player_struct.wall = None;
+        player_anim.face = if side == WallSide::Left {PlayerFace::Right} else {PlayerFace::Left};
+        if inked {
+            spawn_splotch_cluster(&mut splotch_registry, 45, wall_point);
+        }
+        return;
+    }
+
+    // pushing into the wall clings, otherwise slide down it
+    let pushing = actions.move_axis().x * side.sign() > 0.0;
+    let max_fall = if pushing {tuning.cling_speed} else {tuning.slide_speed};
+    if velocity.linvel.y < -max_fall {
+        velocity.linvel.y = -max_fall;
+    }
+    player_anim.face = if side == WallSide::Left {PlayerFace::Left} else {PlayerFace::Right};
+
+    // ink smears down the wall while sliding
+    let mut rng = rand::thread_rng();
+    if velocity.linvel.y < 0.0 && rng.gen_range(0..=8) == 0 {
+        spawn_splotch(&mut splotch_registry, 25, wall_point + Vec2::new(0.0, rng.gen_range(-12.0..12.0)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_character::test_app::*;
+
+    /// A squid in the air against a wall on one side, only wall_slide runs
+    fn wall_app (side: WallSide, fall_speed: f32) -> (App, Entity) {
+        let mut app = squid_app();
+        app.add_systems(Update, wall_slide);
+        let wall = app.world_mut().spawn(Climbable).id();
+        app.world_mut().spawn(WallSensor {
+            side,
+            contacts: HashSet::from([wall]),
+        });
+        let squid = spawn_squid(&mut app);
+        app.world_mut().entity_mut(squid).insert(Velocity::linear(Vec2::new(0.0, -fall_speed)));
+        return (app, squid);
+    }
+
+    #[test]
+    fn sliding_clamps_the_fall_and_pushing_in_clings () {
+        let tuning = WallTuning::default();
+        let (mut app, squid) = wall_app(WallSide::Right, 500.0);
+        app.update();
+        assert_eq!(app.world().get::<Player>(squid).unwrap().wall, Some(WallSide::Right));
+        assert_eq!(app.world().get::<Velocity>(squid).unwrap().linvel.y, -tuning.slide_speed);
+
+        set_move_axis(&mut app, Vec2::X);
+        app.update();
+        assert_eq!(app.world().get::<Velocity>(squid).unwrap().linvel.y, -tuning.cling_speed);
+
+        // falling slower than the slide speed is left alone
+        set_move_axis(&mut app, Vec2::ZERO);
+        app.world_mut().get_mut::<Velocity>(squid).unwrap().linvel.y = -10.0;
+        app.update();
+        assert_eq!(app.world().get::<Velocity>(squid).unwrap().linvel.y, -10.0);
+    }
+
+    #[test]
+    fn wall_jump_pushes_away_from_the_wall () {
+        let tuning = WallTuning::default();
+        for (side, away) in [(WallSide::Left, 1.0), (WallSide::Right, -1.0)] {
+            let (mut app, squid) = wall_app(side, 50.0);
+            hold(&mut app, PlayerAction::Jump, true);
+            app.update();
+
+            let player = app.world().get::<Player>(squid).unwrap();
+            assert_eq!(player.wall, None);
+            assert_eq!(player.wall_jump_lock, tuning.jump_lock);
+            assert!(player.jump_used);
+            assert!(app.world().get::<PlayerAnimation>(squid).unwrap().face == if away > 0.0 {PlayerFace::Right} else {PlayerFace::Left});
+            assert_eq!(app.world().get::<Velocity>(squid).unwrap().linvel, Vec2::new(away * tuning.jump_away, tuning.jump_up));
+            assert_eq!(app.world().get::<InkReserve>(squid).unwrap().ink, 100.0 - InkTuning::default().jump_cost);
+        }
+    }
+
+    #[test]
+    fn grounded_squid_is_not_on_the_wall () {
+        let (mut app, squid) = wall_app(WallSide::Left, 500.0);
+        app.world_mut().get_mut::<Player>(squid).unwrap().grounded = true;
+        app.update();
+        assert_eq!(app.world().get::<Player>(squid).unwrap().wall, None);
+        assert_eq!(app.world().get::<Velocity>(squid).unwrap().linvel.y, -500.0);
+    }
+}
@@ -3,6 +3,8 @@ pub mod platform;
 pub mod water_scene;
 pub mod parallax;
 pub mod layout;
+pub mod water_volume;
 
 pub use platform::*;
-pub use parallax::*;
\ No newline at end of file
+pub use parallax::*;
+pub use water_volume::*;
\ No newline at end of file
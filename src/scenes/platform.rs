@@ -1,12 +1,24 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy_rapier2d::prelude::*;
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (enable_passthrough_hooks, tick_drop_through));
+    }
+}
 
 #[derive(Component)]
 pub struct Platform {
     platform_type: PlatformType
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PlatformType {
     Solid,
+    /// One-way, bodies pass through from below and land on top
     Passthrough
 }
 
@@ -24,4 +36,79 @@ impl Platform {
     pub fn get_type (&self) -> &PlatformType {
         return &self.platform_type;
     }
-}
\ No newline at end of file
+    pub fn is_passthrough (&self) -> bool {
+        return self.platform_type == PlatformType::Passthrough;
+    }
+}
+
+/// Falls through passthrough platforms until the timer runs out, removed afterwards
+#[derive(Component)]
+pub struct DropThrough {
+    pub timer: Timer,
+}
+
+impl DropThrough {
+    pub fn new (duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+}
+
+/// Physics hooks for one-way platforms, pass this to `RapierPhysicsPlugin`
+///
+/// Contacts with a passthrough platform are only kept when they push the other body up,
+/// and dropped entirely while that body has [`DropThrough`]
+#[derive(SystemParam)]
+pub struct OneWayPlatformHooks<'w, 's> {
+    platforms: Query<'w, 's, &'static Platform>,
+    dropping: Query<'w, 's, (), With<DropThrough>>,
+}
+
+impl BevyPhysicsHooks for OneWayPlatformHooks<'_, '_> {
+    fn modify_solver_contacts(&self, context: ContactModificationContextView) {
+        let (other, allowed_normal) = if self.is_passthrough(context.collider1()) {
+            (context.collider2(), Vec2::Y)
+        } else if self.is_passthrough(context.collider2()) {
+            (context.collider1(), Vec2::NEG_Y)
+        } else {
+            return;
+        };
+        if self.dropping.contains(other) {
+            context.raw.solver_contacts.clear();
+            return;
+        }
+        context.raw.update_as_oneway_platform(&allowed_normal.into(), 0.1);
+    }
+}
+
+impl OneWayPlatformHooks<'_, '_> {
+    fn is_passthrough (&self, entity: Entity) -> bool {
+        return self.platforms.get(entity).is_ok_and(|platform| platform.is_passthrough());
+    }
+}
+
+/// Rapier only runs the hooks for colliders that ask for them
+fn enable_passthrough_hooks (
+    mut commands: Commands,
+    platform_query: Query<(Entity, &Platform), Added<Platform>>,
+) {
+    for (entity, platform) in platform_query.iter() {
+        if platform.is_passthrough() {
+            commands.entity(entity).insert(ActiveHooks::MODIFY_SOLVER_CONTACTS);
+        }
+    }
+}
+
+fn tick_drop_through (
+    mut commands: Commands,
+    mut drop_query: Query<(Entity, &mut DropThrough)>,
+    time: Res<Time>,
+) {
+    for (entity, mut drop_through) in drop_query.iter_mut() {
+        drop_through.timer.tick(time.delta());
+        if drop_through.timer.finished() {
+            commands.entity(entity).remove::<DropThrough>();
+        }
+    }
+}
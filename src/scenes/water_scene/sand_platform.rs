@@ -2,22 +2,30 @@ use bevy::prelude::*;
 use crate::scenes::platform::*;
 use bevy_rapier2d::prelude::*;
 use crate::flex_load::*;
+use crate::player_character::tentacle::Grappleable;
+use crate::player_character::wall::Climbable;
 
+/// Passthrough platforms can't be climbed, the squid would cling to a ledge it is meant to jump through
 pub fn spawn_sand_platform (
     commands: &mut Commands,
     loaded_assets: &Res<LoadedAssets>,
     position: Vec3,
+    platform: Platform,
 ) {
-
-    commands.spawn((
+    let climbable = !platform.is_passthrough();
+    let mut platform_entity = commands.spawn((
         Sprite {
-            image: loaded_assets.get_typed::<Image>("sand").unwrap(),
+            image: loaded_assets.get_typed_or_default::<Image>("sand"),
             custom_size: Some(Vec2::new(256.0, 64.0)),
             ..default()
         },
         Transform::from_translation(position),
         RigidBody::Fixed,
         Collider::cuboid(128., 32.),
-        Platform::SOLID,
-));
-}
\ No newline at end of file
+        platform,
+        Grappleable,
+    ));
+    if climbable {
+        platform_entity.insert(Climbable);
+    }
+}
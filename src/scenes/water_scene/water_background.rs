@@ -1,11 +1,12 @@
 use bevy::prelude::*;
-use crate::{flex_load::*, ParallaxLayer, BACKGROUND_Z};
+use crate::{flex_load::*, scenes::ParallaxLayer, BACKGROUND_Z};
+use super::water_scene::WATER_SCENE_GROUP;
 
 pub struct WaterSceneBackgroundPlugin;
 
 impl Plugin for WaterSceneBackgroundPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AssetLoadState::Ready), spawn_background_layers);
+        app.add_systems(Update, spawn_background_layers.run_if(asset_group_just_ready(WATER_SCENE_GROUP)));
     }
 }
 
@@ -17,7 +18,7 @@ fn spawn_background_layers (
 ) {
     commands.spawn((
         Sprite {
-            image: loaded_assets.get_typed::<Image>("background").unwrap(),
+            image: loaded_assets.get_typed_or_default::<Image>("background"),
             custom_size: Some(Vec2::new(1280.0, 720.0)),
             ..default()
         },
@@ -32,7 +33,7 @@ fn spawn_background_layers (
     for i in -3..=3 {
         commands.spawn((
             Sprite {
-                image: loaded_assets.get_typed::<Image>("reef_far").unwrap(),
+                image: loaded_assets.get_typed_or_default::<Image>("reef_far"),
                 custom_size: Some(Vec2::new(1300.0, 400.0)),
                 ..default()
             },
@@ -46,7 +47,7 @@ fn spawn_background_layers (
 
         commands.spawn((
             Sprite {
-                image: loaded_assets.get_typed::<Image>("light_beams").unwrap(),
+                image: loaded_assets.get_typed_or_default::<Image>("light_beams"),
                 custom_size: Some(Vec2::new(300.0, 1000.0)),
                 color: Color::srgba(1.0, 1.0, 0.9, 0.05),
                 ..default()
@@ -61,7 +62,7 @@ fn spawn_background_layers (
 
         commands.spawn((
             Sprite {
-                image: loaded_assets.get_typed::<Image>("reef").unwrap(),
+                image: loaded_assets.get_typed_or_default::<Image>("reef"),
                 custom_size: Some(Vec2::new(1000.0, 300.0)),
                 ..default()
             },
@@ -74,7 +75,7 @@ fn spawn_background_layers (
         ));
         commands.spawn((
             Sprite {
-                image: loaded_assets.get_typed::<Image>("watertop").unwrap(),
+                image: loaded_assets.get_typed_or_default::<Image>("watertop"),
                 custom_size: Some(Vec2::new(375.0, 150.0)),
                 ..default()
             },
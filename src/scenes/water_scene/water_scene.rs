@@ -2,22 +2,42 @@ use bevy::prelude::*;
 
 use super::{sand_platform::*, water_background::WaterSceneBackgroundPlugin};
 use crate::{flex_load::*, PLATFORM_Z, scenes::*};
+use crate::player_character::ink_reserve::spawn_ink_pickup;
 
+/// Loads its own asset group and spawns once that group is ready
+///
+/// The manifest preloads the group as well, so the floor is there by the time the squid spawns
 pub struct WaterScenePlugin;
 
 impl Plugin for WaterScenePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((WaterSceneBackgroundPlugin, ParallaxPlugin));
-        app.add_systems(OnEnter(AssetLoadState::Ready), spawn_four_platforms);
+        app.add_systems(Startup, request_water_scene_assets);
+        app.add_systems(Update, spawn_platforms.run_if(asset_group_just_ready(WATER_SCENE_GROUP)));
     }
 }
 
-fn spawn_four_platforms (
+/// Asset group with the water scene's backgrounds and platforms, see assets/manifest.ron
+pub const WATER_SCENE_GROUP: &str = "water_scene";
+
+fn request_water_scene_assets (
+    mut loaded_assets: ResMut<LoadedAssets>,
+) {
+    loaded_assets.request_group(WATER_SCENE_GROUP);
+}
+
+fn spawn_platforms (
     mut commands: Commands,
     loaded_assets: Res<LoadedAssets>,
 ) {
-    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(0.0, -64.0, PLATFORM_Z));
-    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(-500.0, 228.0, PLATFORM_Z));
-    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(500.0, 228.0, PLATFORM_Z));
-    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(0.0, 556.0, PLATFORM_Z));
+    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(0.0, -64.0, PLATFORM_Z), Platform::SOLID);
+    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(-500.0, 228.0, PLATFORM_Z), Platform::SOLID);
+    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(500.0, 228.0, PLATFORM_Z), Platform::SOLID);
+    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(0.0, 556.0, PLATFORM_Z), Platform::SOLID);
+    // jump up through it from the bottom platform, dive and jump to drop back down
+    spawn_sand_platform(&mut commands, &loaded_assets, Vec3::new(0.0, 228.0, PLATFORM_Z), Platform::PASSTHROUGH);
+    // deep water under the bottom platform, catches the squid before it falls out of bounds
+    spawn_water_volume(&mut commands, Vec2::new(0.0, -500.0), Vec2::new(2400.0, 800.0));
+    spawn_ink_pickup(&mut commands, Vec2::new(-500.0, 290.0), 40.0);
+    spawn_ink_pickup(&mut commands, Vec2::new(500.0, 290.0), 40.0);
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+/// Area of water, the squid swims while its center is inside one
+#[derive(Component)]
+pub struct WaterVolume {
+    /// Full width and height, centered on the entity's transform
+    pub size: Vec2,
+}
+
+impl WaterVolume {
+    pub fn new (size: Vec2) -> Self {
+        Self {
+            size,
+        }
+    }
+    pub fn contains (&self, center: Vec2, point: Vec2) -> bool {
+        let offset = (point - center).abs();
+        return offset.x <= self.size.x / 2.0 && offset.y <= self.size.y / 2.0;
+    }
+}
+
+pub fn spawn_water_volume (
+    commands: &mut Commands,
+    position: Vec2,
+    size: Vec2,
+) {
+    commands.spawn((
+        Name::new("water volume"),
+        WaterVolume::new(size),
+        Transform::from_translation(position.extend(0.0)),
+    ));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_points_inside_and_on_the_edge () {
+        let water = WaterVolume::new(Vec2::new(200.0, 100.0));
+        let center = Vec2::new(0.0, -500.0);
+        assert!(water.contains(center, center));
+        assert!(water.contains(center, Vec2::new(100.0, -450.0)));
+        assert!(water.contains(center, Vec2::new(-99.0, -549.0)));
+        assert!(!water.contains(center, Vec2::new(101.0, -500.0)));
+        assert!(!water.contains(center, Vec2::new(0.0, -440.0)));
+    }
+}